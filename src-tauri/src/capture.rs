@@ -5,46 +5,143 @@ use base64::{Engine as _, engine::general_purpose};
 // --- 新增：引入 png 库的相关模块以进行性能优化 ---
 use png::Compression;
 
-/// 捕获主显示器的全屏图像。
+use crate::error::AppError;
+use crate::i18n::t;
+
+/// 一次全屏截图的结果。
+///
+/// `x` / `y` 为截图区域左上角在虚拟桌面中的物理坐标（副屏位于主屏左侧或上方时可能为负数），
+/// 截图窗口需要放置到该位置才能与屏幕内容重合。
+pub struct CaptureResult {
+    pub image: RgbaImage,
+    pub x: i32,
+    pub y: i32,
+}
+
+/// 按照截图范围设置捕获屏幕图像。
+///
+/// # 参数
+/// - `scope`: `"all"` 表示拼接所有显示器组成的整个虚拟桌面，其余值表示仅捕获鼠标所在的显示器。
 ///
 /// # 返回
 ///
-/// `Result<RgbaImage, String>`:
-/// - `Ok(RgbaImage)`: 成功捕获到的 RGBA 格式的图像缓冲区。
-/// - `Err(String)`: 捕获过程中发生的错误信息。
-pub fn capture_fullscreen() -> Result<RgbaImage, String> {
-    // 1. 获取所有连接的显示器
-    let monitors = Monitor::all().map_err(|e| format!("无法获取显示器列表: {}", e))?;
-    if monitors.is_empty() {
-        return Err("未找到任何显示器".to_string());
+/// `Result<CaptureResult, AppError>`:
+/// - `Ok(CaptureResult)`: 成功捕获到的图像及其在虚拟桌面中的原点。
+/// - `Err(AppError)`: 捕获过程中发生的错误。
+pub fn capture_fullscreen(scope: &str) -> Result<CaptureResult, AppError> {
+    match scope {
+        "all" => capture_virtual_desktop(),
+        _ => capture_cursor_monitor(),
     }
+}
+
+/// 捕获鼠标所在的显示器，无法获取鼠标位置时退回到主显示器。
+fn capture_cursor_monitor() -> Result<CaptureResult, AppError> {
+    // 1. 获取所有连接的显示器
+    let monitors = all_monitors()?;
 
-    // 2. 查找主显示器
-    let primary_monitor = monitors.into_iter()
-        .find(|m| m.is_primary().unwrap_or(false))
-        .or_else(|| Monitor::all().ok()?.into_iter().next()) // 如果没有主显示器，就用第一个
-        .ok_or_else(|| "无法确定要捕获的显示器".to_string())?;
+    // 2. 查找鼠标所在的显示器，其次是主显示器，最后是第一个显示器
+    let cursor_monitor = cursor_position().and_then(|(cx, cy)| {
+        monitors.iter().find(|m| {
+            let (mx, my) = (m.x().unwrap_or(0), m.y().unwrap_or(0));
+            let (mw, mh) = (m.width().unwrap_or(0) as i32, m.height().unwrap_or(0) as i32);
+            cx >= mx && cx < mx + mw && cy >= my && cy < my + mh
+        })
+    });
+    let target_monitor = cursor_monitor
+        .or_else(|| monitors.iter().find(|m| m.is_primary().unwrap_or(false)))
+        .or_else(|| monitors.first())
+        .ok_or(AppError::NoMonitor)?;
 
-    let monitor_name = primary_monitor.name().unwrap_or_else(|_| "未知名称".to_string());
-    let monitor_width = primary_monitor.width().unwrap_or(0);
-    let monitor_height = primary_monitor.height().unwrap_or(0);
+    let monitor_name = target_monitor.name().unwrap_or_else(|_| t("name.unknown_monitor"));
+    let monitor_x = target_monitor.x().unwrap_or(0);
+    let monitor_y = target_monitor.y().unwrap_or(0);
 
     println!(
-        "准备在主显示器上截图: (名称={}, 尺寸={}x{})",
+        "准备在显示器上截图: (名称={}, 位置=({}, {}), 尺寸={}x{})",
         monitor_name,
-        monitor_width,
-        monitor_height
+        monitor_x,
+        monitor_y,
+        target_monitor.width().unwrap_or(0),
+        target_monitor.height().unwrap_or(0)
     );
 
     // 3. 执行截图操作
-    let image = primary_monitor
+    let image = target_monitor
         .capture_image()
-        .map_err(|e| format!("在显示器 '{}' 上截图失败: {}", monitor_name, e))?;
+        .map_err(|e| AppError::CaptureFailed { monitor: monitor_name.clone(), detail: e.to_string() })?;
 
     println!("全屏截图成功，图像尺寸: {}x{}", image.width(), image.height());
 
-    // 4. 返回图像
-    Ok(image)
+    // 4. 返回图像及显示器原点
+    Ok(CaptureResult { image, x: monitor_x, y: monitor_y })
+}
+
+/// 捕获所有显示器，并按照各自在虚拟桌面中的位置拼接成一张图像。
+fn capture_virtual_desktop() -> Result<CaptureResult, AppError> {
+    let monitors = all_monitors()?;
+
+    // 1. 逐个截图，并记录每个显示器的原点
+    let mut captures = Vec::with_capacity(monitors.len());
+    for monitor in &monitors {
+        let image = monitor.capture_image().map_err(|e| AppError::CaptureFailed {
+            monitor: monitor.name().unwrap_or_else(|_| t("name.unknown_monitor")),
+            detail: e.to_string(),
+        })?;
+        captures.push((monitor.x().unwrap_or(0), monitor.y().unwrap_or(0), image));
+    }
+
+    // 2. 计算虚拟桌面的包围盒（原点可能为负数）
+    let min_x = captures.iter().map(|(x, _, _)| *x).min().unwrap_or(0);
+    let min_y = captures.iter().map(|(_, y, _)| *y).min().unwrap_or(0);
+    let max_x = captures.iter().map(|(x, _, img)| *x + img.width() as i32).max().unwrap_or(0);
+    let max_y = captures.iter().map(|(_, y, img)| *y + img.height() as i32).max().unwrap_or(0);
+
+    let width = (max_x - min_x) as u32;
+    let height = (max_y - min_y) as u32;
+    println!(
+        "准备拼接虚拟桌面: 显示器数量={}, 原点=({}, {}), 尺寸={}x{}",
+        captures.len(), min_x, min_y, width, height
+    );
+
+    // 3. 将各显示器的图像绘制到以包围盒左上角为原点的画布上
+    let mut canvas = RgbaImage::new(width, height);
+    for (x, y, image) in &captures {
+        image::imageops::overlay(&mut canvas, image, (*x - min_x) as i64, (*y - min_y) as i64);
+    }
+
+    println!("虚拟桌面截图成功，图像尺寸: {}x{}", canvas.width(), canvas.height());
+    Ok(CaptureResult { image: canvas, x: min_x, y: min_y })
+}
+
+/// 获取所有连接的显示器，一个都没有时返回错误。
+fn all_monitors() -> Result<Vec<Monitor>, AppError> {
+    let monitors = Monitor::all().map_err(|e| AppError::MonitorsUnavailable { detail: e.to_string() })?;
+    if monitors.is_empty() {
+        return Err(AppError::NoMonitor);
+    }
+    Ok(monitors)
+}
+
+/// 获取鼠标在虚拟桌面中的物理坐标。
+#[cfg(windows)]
+fn cursor_position() -> Option<(i32, i32)> {
+    use windows_sys::Win32::Foundation::POINT;
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+    let mut point = POINT { x: 0, y: 0 };
+    // SAFETY: `point` 是一个有效的可写 POINT 结构体。
+    if unsafe { GetCursorPos(&mut point) } != 0 {
+        Some((point.x, point.y))
+    } else {
+        None
+    }
+}
+
+/// 非 Windows 平台暂不支持获取鼠标位置，由调用方退回到主显示器。
+#[cfg(not(windows))]
+fn cursor_position() -> Option<(i32, i32)> {
+    None
 }
 
 
@@ -55,10 +152,10 @@ pub fn capture_fullscreen() -> Result<RgbaImage, String> {
 ///
 /// # 返回
 ///
-/// `Result<String, String>`:
+/// `Result<String, AppError>`:
 /// - `Ok(String)`: 格式为 "data:image/png;base64,..." 的字符串。
-/// - `Err(String)`: 编码过程中发生的错误。
-pub fn encode_image_to_data_url(image: &RgbaImage) -> Result<String, String> {
+/// - `Err(AppError)`: 编码过程中发生的错误。
+pub fn encode_image_to_data_url(image: &RgbaImage) -> Result<String, AppError> {
     let mut buffer = Cursor::new(Vec::new());
 
     // --- 核心性能优化：使用 png 库并设置快速压缩 ---
@@ -71,11 +168,10 @@ pub fn encode_image_to_data_url(image: &RgbaImage) -> Result<String, String> {
     encoder.set_compression(Compression::Fast);
 
     // 3. 获取编码器的写入器并写入图像数据
-    let mut writer = encoder.write_header()
-        .map_err(|e| format!("写入PNG头失败: {}", e))?;
-    writer.write_image_data(image.as_raw())
-        .map_err(|e| format!("写入PNG图像数据失败: {}", e))?;
-    writer.finish().map_err(|e| format!("完成PNG编码失败: {}", e))?;
+    let encode_failed = |e: png::EncodingError| AppError::ImageEncodeFailed { detail: e.to_string() };
+    let mut writer = encoder.write_header().map_err(encode_failed)?;
+    writer.write_image_data(image.as_raw()).map_err(encode_failed)?;
+    writer.finish().map_err(encode_failed)?;
     // --- 优化结束 ---
 
     // 4. 对内存中的 PNG 数据进行 Base64 编码
@@ -2,21 +2,30 @@
 
 use serde::{Serialize};
 use tauri::{Manager, State};
-use std::process::Command as StdCommand;
 use std::fs;
 use std::io::Write;
 use base64::{Engine as _, engine::general_purpose};
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 use tauri::api::notification::Notification;
 use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::ImageViewerPayload;
+use crate::{ImageViewerPayload, TranslationUpdatePayload};
+use crate::error::AppError;
+use crate::glossary::ProtectedText;
+use crate::i18n::{t, tf};
+use crate::jobs;
 use crate::settings::{AppSettings, AppState, LastOcrResult, copy_image_to_clipboard, save_image_to_desktop};
-use crate::translator;
-
-#[cfg(windows)]
-use std::os::windows::process::CommandExt;
+use crate::installs::{self, InstalledEngine, InstalledVersion};
+use crate::manifest::{self, ArchiveType, Artifact, EngineSpec};
+use crate::ocr::{self, OcrEngine, OcrEngineInfo, OcrResult, RAPIDOCR_ENGINE_ID};
+use crate::layout;
+use crate::segmenter;
+use crate::translation_cache::{self, CacheStats};
+use crate::translator::{self, FallbackTranslator, LanguagePair, Translator};
 
 // --- 事件 Payload 定义 ---
 #[derive(Clone, Serialize)]
@@ -27,18 +36,21 @@ struct DownloadProgressPayload {
     target: Option<String>,
 }
 
+// 翻译进行中的阶段性译文，completed / total 为已完成的片段数与总片段数
+#[derive(Clone, Serialize)]
+struct TranslationProgressPayload {
+    job_id: u64,
+    translated_text: String,
+    completed: usize,
+    total: usize,
+}
+
 // --- 常量定义 ---
-// OCR 引擎 (RapidOCR)
-const OCR_URL: &str = "https://github.com/hiroi-sora/RapidOCR-json/releases/download/v0.2.0/RapidOCR-json_v0.2.0.7z";
-const OCR_EXE_NAME: &str = "RapidOCR-json.exe";
-// 定义解压后的子目录名
-const OCR_DIR_NAME: &str = "RapidOCR-json_v0.2.0";
-
-// 翻译引擎 (LocalTranslator)
-// 修改：升级到 0.2.0 版本，不再使用 7z 格式，改为两个文件
-const TRANSLATOR_PACKAGES_URL: &str = "https://github.com/git-hub-cc/LocalTranslator/releases/download/V0.2.0/packages.zip";
-const TRANSLATOR_EXE_URL: &str = "https://github.com/git-hub-cc/LocalTranslator/releases/download/V0.2.0/translate_engine_cpu.exe";
-const TRANSLATOR_EXE_NAME: &str = "translate_engine.exe";
+// 引擎的下载地址、安装目录与可执行文件由引擎清单 (manifest 模块) 提供
+
+// 下载中断时的最大重试次数，两次重试间的等待时间从 DOWNLOAD_RETRY_BASE_DELAY 开始逐次翻倍
+const DOWNLOAD_MAX_RETRIES: u32 = 5;
+const DOWNLOAD_RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
 
 // --- Tauri 命令定义 ---
 
@@ -50,196 +62,701 @@ pub fn get_last_ocr_result(state: State<AppState>) -> Option<LastOcrResult> {
 
 // --- OCR 引擎管理 ---
 #[tauri::command]
-pub async fn check_ocr_status(app: tauri::AppHandle) -> Result<bool, String> {
-    let local_data_dir = app.path_resolver().app_local_data_dir()
-        .ok_or("无法获取本地数据目录")?;
-    // 在路径中加入子目录
-    let exe_path = local_data_dir.join(OCR_DIR_NAME).join(OCR_EXE_NAME);
+pub async fn check_ocr_status(app: tauri::AppHandle) -> Result<bool, AppError> {
+    let exe_path = manifest::entry_path(&app, RAPIDOCR_ENGINE_ID)?;
     let exists = exe_path.exists();
     println!("[STATUS] 检查 OCR 状态: 路径='{:?}', 是否存在={}", exe_path, exists);
     Ok(exists)
 }
 
 #[tauri::command]
-pub async fn download_ocr(app: tauri::AppHandle) -> Result<(), String> {
-    println!("[DOWNLOAD_OCR] 开始下载 OCR 引擎...");
-    let window = app.get_window("main").ok_or("找不到主窗口")?;
-    let local_data_dir = app.path_resolver().app_local_data_dir().ok_or("无法获取本地数据目录")?;
-    println!("[DOWNLOAD_OCR] 本地数据目录: {:?}", local_data_dir);
-    if !local_data_dir.exists() {
-        println!("[DOWNLOAD_OCR] 目录不存在，正在创建...");
-        fs::create_dir_all(&local_data_dir).map_err(|e| format!("创建目录失败: {}", e))?;
-    }
-    let archive_path = local_data_dir.join("ocr.7z");
-    println!("[DOWNLOAD_OCR] 存档将保存到: {:?}", archive_path);
-
-    // 1. 下载文件
-    println!("[DOWNLOAD_OCR] 正在从 URL 下载: {}", OCR_URL);
-    let client = reqwest::Client::new();
-    let res = client.get(OCR_URL).send().await.map_err(|e| {
-        let err_msg = format!("请求失败: {}", e);
-        println!("[DOWNLOAD_OCR] 错误: {}", err_msg);
-        err_msg
-    })?;
-    let total_size = res.content_length().unwrap_or(0);
-    println!("[DOWNLOAD_OCR] 文件总大小: {} bytes", total_size);
-
-    let mut downloaded: u64 = 0;
-    let mut stream = res.bytes_stream();
-    let mut file = fs::File::create(&archive_path).map_err(|e| {
-        let err_msg = format!("创建文件失败: {}", e);
-        println!("[DOWNLOAD_OCR] 错误: {}", err_msg);
-        err_msg
-    })?;
-
-    while let Some(item) = stream.next().await {
-        let chunk = item.map_err(|e| {
-            let err_msg = format!("下载流出错: {}", e);
-            println!("[DOWNLOAD_OCR] 错误: {}", err_msg);
-            err_msg
-        })?;
-        file.write_all(&chunk).map_err(|e| {
-            let err_msg = format!("写入文件块失败: {}", e);
-            println!("[DOWNLOAD_OCR] 错误: {}", err_msg);
-            err_msg
-        })?;
-        downloaded += chunk.len() as u64;
-        window.emit("ocr-download-progress", DownloadProgressPayload {
-            progress: downloaded, total: total_size, status: "downloading".to_string(),
-            target: Some("ocr".to_string()),
-        }).unwrap_or(());
-    }
-    file.flush().map_err(|e| format!("刷新文件失败: {}", e))?;
-    file.sync_all().map_err(|e| format!("同步文件失败: {}", e))?;
-    println!("[DOWNLOAD_OCR] 下载完成. 总共下载 {} bytes", downloaded);
-
-    // 2. 解压文件 (.7z)
-    println!("[DOWNLOAD_OCR] 开始解压文件: {:?}", archive_path);
-    window.emit("ocr-download-progress", DownloadProgressPayload {
-        progress: total_size, total: total_size, status: "extracting".to_string(),
-        target: Some("ocr".to_string()),
-    }).unwrap_or(());
-
-    sevenz_rust::decompress_file(&archive_path, &local_data_dir)
-        .map_err(|e| {
-            let err_msg = format!("解压7z文件失败: {:?}", e);
-            println!("[DOWNLOAD_OCR] 错误: {}", err_msg);
-            err_msg
-        })?;
-    println!("[DOWNLOAD_OCR] 解压成功到: {:?}", local_data_dir);
-
-    // 3. 清理并通知完成
-    println!("[DOWNLOAD_OCR] 删除临时存档: {:?}", archive_path);
-    let _ = fs::remove_file(archive_path);
-    println!("[DOWNLOAD_OCR] OCR 引擎安装流程完成.");
-    window.emit("ocr-download-progress", DownloadProgressPayload {
-        progress: total_size, total: total_size, status: "completed".to_string(),
-        target: Some("ocr".to_string()),
-    }).unwrap_or(());
+pub fn list_ocr_engines(state: State<AppState>) -> Vec<OcrEngineInfo> {
+    ocr::available_engines(&state.settings.lock().unwrap())
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn download_ocr(app: tauri::AppHandle) -> Result<(), AppError> {
+    download_engine(&app, RAPIDOCR_ENGINE_ID, "ocr-download-progress").await
 }
 
 
 // --- 翻译引擎管理 ---
 #[tauri::command]
-pub async fn check_translator_status(app: tauri::AppHandle) -> Result<bool, String> {
-    let local_data_dir = app.path_resolver().app_local_data_dir()
-        .ok_or("无法获取本地数据目录")?;
-    // 注意：如果新版 7z 解压后有一层文件夹（如 LocalTranslator-0.2.0），可能需要调整此路径
-    // 目前保持原逻辑，假设 exe 直接或间接位于我们预期的位置
-    let exe_path = local_data_dir.join(TRANSLATOR_EXE_NAME);
+pub async fn check_translator_status(app: tauri::AppHandle) -> Result<bool, AppError> {
+    let exe_path = manifest::entry_path(&app, translator::TRANSLATOR_WORKER_NAME)?;
     Ok(exe_path.exists())
 }
 
 #[tauri::command]
-pub async fn download_translator(app: tauri::AppHandle) -> Result<(), String> {
-    let window = app.get_window("main").ok_or("找不到主窗口")?;
-    let local_data_dir = app.path_resolver().app_local_data_dir().ok_or("无法获取本地数据目录")?;
-    if !local_data_dir.exists() {
-        fs::create_dir_all(&local_data_dir).map_err(|e| format!("创建目录失败: {}", e))?;
+pub async fn get_supported_language_pairs(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<Vec<LanguagePair>, AppError> {
+    let settings = state.settings.lock().unwrap().clone();
+    translator::get_translator(&app, &settings).supported_pairs().await
+}
+
+#[tauri::command]
+pub fn get_translation_cache_stats(state: State<AppState>) -> CacheStats {
+    state.translation_cache.lock().unwrap().stats()
+}
+
+#[tauri::command]
+pub fn purge_translation_cache(state: State<AppState>) -> Result<(), AppError> {
+    println!("[CACHE] 清空翻译缓存");
+    state.translation_cache.lock().unwrap().purge()
+}
+
+#[tauri::command]
+pub async fn download_translator(app: tauri::AppHandle) -> Result<(), AppError> {
+    download_engine(&app, translator::TRANSLATOR_WORKER_NAME, "download-progress").await
+}
+
+/// 按引擎清单下载、校验并安装当前平台的所有安装包。
+///
+/// 新版本安装在独立的版本目录中 (已安装的版本保持不变)，启用后运行自检，
+/// 自检失败时恢复到之前的版本。进度通过 `event` 事件通知前端，事件的 target 为安装包 ID。
+async fn download_engine(app: &tauri::AppHandle, engine_id: &str, event: &str) -> Result<(), AppError> {
+    let window = main_window(app)?;
+    manifest::apply_mirror(app).await?;
+    let local_data_dir = manifest::local_data_dir(app)?;
+    let engine = manifest::engine_spec(app, engine_id)?;
+    let artifacts = engine.platform_artifacts()
+        .ok_or_else(|| AppError::UnsupportedPlatform { engine: engine.id.clone(), platform: manifest::current_platform() })?;
+    // 未登记校验值的安装包下载后也无法安装，开始下载前就拒绝
+    if let Some(artifact) = artifacts.iter().find(|a| !a.has_checksum()) {
+        return Err(AppError::ChecksumMissing { file: artifact.url_file_name().to_string() });
     }
+    fs::create_dir_all(&local_data_dir)?;
+    println!("[DOWNLOAD] 开始下载 {} {}", engine.name, engine.version);
+
+    let emit = |target: &str, progress: u64, status: &str| {
+        window.emit(event, DownloadProgressPayload {
+            progress, total: progress, status: status.to_string(),
+            target: Some(target.to_string()),
+        }).unwrap_or(());
+    };
 
+    // 1. 下载并校验所有安装包，全部成功后才改动已安装的引擎
     let client = reqwest::Client::new();
+    let mut downloads = Vec::new();
+    for artifact in artifacts {
+        // 下载到 .part 文件 (文件名带版本号，避免续传到旧版本的文件上)
+        println!("[DOWNLOAD] 正在下载 {}: {}", artifact.id, artifact.url);
+        let part_path = local_data_dir.join(format!("{}-{}-{}.part", engine.id, artifact.id, engine.version));
+        let total_size = download_resumable(&client, &window, &artifact.url, &part_path, artifact.size, event, &artifact.id).await?;
+        println!("[DOWNLOAD] 下载完成. 总共下载 {} bytes", total_size);
+
+        // 校验通过前不解压，避免安装损坏或被篡改的引擎
+        emit(&artifact.id, total_size, "verifying");
+        verify_artifact(&part_path, artifact).await?;
+        downloads.push((artifact, part_path, total_size));
+    }
 
-    // 1. 下载并提取 packages.zip
-    println!("[DOWNLOAD_TRANS] 正在下载包文件: {}", TRANSLATOR_PACKAGES_URL);
-    let packages_path = local_data_dir.join("packages.zip");
-    
-    let res = client.get(TRANSLATOR_PACKAGES_URL).send().await.map_err(|e| format!("请求包文件失败: {}", e))?;
-    let total_size = res.content_length().unwrap_or(0);
-    let mut downloaded: u64 = 0;
-    let mut stream = res.bytes_stream();
-    let mut file = fs::File::create(&packages_path).map_err(|e| format!("创建包文件失败: {}", e))?;
+    // 2. 解压或复制到新版本的目录
+    let (old_record, new_version) = new_engine_version(app, &engine).await;
+    let install_dir = new_version.install_path(&local_data_dir);
+    println!("[DOWNLOAD] 安装到: {:?}", install_dir);
+    fs::create_dir_all(&install_dir)?;
+    for (artifact, part_path, total_size) in &downloads {
+        emit(&artifact.id, *total_size, "extracting");
+        let installed = match artifact.archive {
+            ArchiveType::SevenZ => extract_7z(part_path, &install_dir),
+            ArchiveType::Zip => extract_zip(part_path, &install_dir),
+            ArchiveType::File => fs::rename(part_path, install_dir.join(artifact.target_file_name())).map_err(AppError::from),
+        };
+        let _ = fs::remove_file(part_path);
+        if let Err(e) = installed {
+            eprintln!("[DOWNLOAD] 错误: {}", e);
+            let _ = fs::remove_dir_all(&install_dir);
+            return Err(e);
+        }
+    }
 
-    while let Some(item) = stream.next().await {
-        let chunk = item.map_err(|e| format!("下载包文件出错: {}", e))?;
-        file.write_all(&chunk).map_err(|e| format!("写入包文件失败: {}", e))?;
-        downloaded += chunk.len() as u64;
-        window.emit("download-progress", DownloadProgressPayload {
-            progress: downloaded, total: total_size, status: "downloading".to_string(),
-            target: Some("packages".to_string()),
-        }).unwrap_or(());
+    // 3. 启用新版本并自检
+    let (last_artifact, _, last_size) = downloads.last()
+        .ok_or_else(|| AppError::UnsupportedPlatform { engine: engine.id.clone(), platform: manifest::current_platform() })?;
+    emit(&last_artifact.id, *last_size, "testing");
+    activate_engine_version(app, &engine, new_version, old_record).await?;
+    for (artifact, _, total_size) in &downloads {
+        emit(&artifact.id, *total_size, "completed");
+    }
+    println!("[DOWNLOAD] {} {} 安装流程完成.", engine.name, engine.version);
+    Ok(())
+}
+
+/// 为引擎的新版本分配独立的安装目录，返回 (原有的安装记录, 新版本)。
+/// 先关闭正在运行的常驻引擎，避免新旧版本同时运行。
+async fn new_engine_version(app: &tauri::AppHandle, engine: &EngineSpec) -> (Option<InstalledEngine>, InstalledVersion) {
+    let state = app.state::<AppState>();
+    state.engine_workers.shutdown(&engine.id).await;
+    let old_record = state.engine_installs.lock().unwrap().get(&engine.id).cloned();
+    let new_version = InstalledVersion::new(
+        &engine.version,
+        installs::new_version_dir(&engine.id, &engine.version, old_record.as_ref()),
+        &engine.entry,
+    );
+    (old_record, new_version)
+}
+
+/// 将已安装好文件的新版本启用为当前版本并运行自检。
+///
+/// 之前的版本 (没有安装记录时为旧版程序安装在清单目录中的引擎) 作为回退版本保留；
+/// 自检失败时恢复原有的安装记录并删除新版本的目录。
+async fn activate_engine_version(
+    app: &tauri::AppHandle,
+    engine: &EngineSpec,
+    new_version: InstalledVersion,
+    old_record: Option<InstalledEngine>,
+) -> Result<(), AppError> {
+    let local_data_dir = manifest::local_data_dir(app)?;
+    let install_dir = new_version.install_path(&local_data_dir);
+    let state = app.state::<AppState>();
+    let rollback_to = old_record.as_ref().map(|r| r.current.clone()).or_else(|| {
+        engine.entry_path(&local_data_dir).exists()
+            .then(|| InstalledVersion { version: String::new(), dir: engine.install_dir.clone(), entry: engine.entry.clone(), installed_at: 0 })
+    });
+    let record = InstalledEngine { current: new_version, previous: rollback_to.clone() };
+    state.engine_installs.lock().unwrap().set(&engine.id, Some(record.clone()))?;
+
+    let timeout = state.settings.lock().unwrap().engine_timeout();
+    if let Err(e) = smoke_test_engine(app, &engine.id, timeout).await {
+        eprintln!("[INSTALL] {} {} 自检失败: {}", engine.name, engine.version, e);
+        state.engine_workers.shutdown(&engine.id).await;
+        state.engine_installs.lock().unwrap().set(&engine.id, old_record)?;
+        let _ = fs::remove_dir_all(&install_dir);
+        return Err(AppError::SmokeTestFailed {
+            engine: engine.id.clone(),
+            version: engine.version.clone(),
+            restored: rollback_to.map(|previous| previous.version),
+            error: Box::new(e),
+        });
+    }
+
+    installs::remove_unused_versions(&local_data_dir, &engine.id, &record);
+    // 旧版程序安装的引擎不在版本目录中，不再作为回退版本后单独删除
+    let legacy = old_record.iter()
+        .flat_map(|r| std::iter::once(&r.current).chain(r.previous.as_ref()))
+        .find(|v| v.version.is_empty() && !installs::references(&record, v));
+    if let Some(legacy) = legacy {
+        installs::remove_legacy_install(&local_data_dir, legacy);
+    }
+    Ok(())
+}
+
+/// 检查新安装的引擎能否正常运行：OCR 引擎识别一张空白图片，翻译引擎翻译一个单词。
+async fn smoke_test_engine(app: &tauri::AppHandle, engine_id: &str, timeout: Duration) -> Result<(), AppError> {
+    match engine_id {
+        RAPIDOCR_ENGINE_ID => {
+            let image_path = std::env::temp_dir().join("screentranslator-smoke-test.png");
+            image::RgbaImage::from_pixel(64, 32, image::Rgba([255, 255, 255, 255]))
+                .save(&image_path)
+                .map_err(|e| AppError::Io { detail: e.to_string() })?;
+            let result = ocr::RapidOcrEngine::new(app.clone(), timeout)
+                .recognize(&image_path.to_string_lossy())
+                .await;
+            let _ = fs::remove_file(&image_path);
+            match result {
+                // 空白图片识别不到文字是正常结果
+                Ok(_) | Err(AppError::NoTextFound) => Ok(()),
+                Err(e) => Err(e),
+            }
+        }
+        translator::TRANSLATOR_WORKER_NAME => {
+            let local = translator::LocalTranslator::new(app.clone(), false, timeout);
+            let pair = local.supported_pairs().await?.into_iter().next()
+                .ok_or(AppError::NoLanguagePacks)?;
+            local.translate("Hello", &pair).await.map(|_| ())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct EngineUpdateInfo {
+    id: String,
+    name: String,
+    installed: bool,
+    // 已安装的版本，没有记录时 (旧版程序安装的引擎) 为 None
+    installed_version: Option<String>,
+    latest_version: String,
+    update_available: bool,
+}
+
+/// 检查本地引擎是否有新版本。设置了清单镜像地址时先从镜像获取最新的清单。
+#[tauri::command]
+pub async fn check_engine_updates(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<Vec<EngineUpdateInfo>, AppError> {
+    // 镜像上的清单只用于比较版本，下载或更新引擎时才启用
+    let mirror_url = state.settings.lock().unwrap().engine_manifest_url.trim().to_string();
+    let engines = if mirror_url.is_empty() {
+        state.engine_manifest.lock().unwrap().engines.clone()
+    } else {
+        manifest::EngineManifest::fetch(&mirror_url).await?.engines
+    };
+    let mut updates = Vec::new();
+    for engine in engines {
+        // 是否已安装按当前使用的清单与安装记录判断
+        let installed = manifest::entry_path(&app, &engine.id).is_ok_and(|path| path.exists());
+        let installed_version = state.engine_installs.lock().unwrap().get(&engine.id)
+            .map(|r| r.current.version.clone())
+            .filter(|v| !v.is_empty());
+        // 版本未知的旧版安装同样提示更新，更新后即按版本记录
+        let update_available = installed && installed_version.as_deref()
+            .is_none_or(|v| manifest::is_newer_version(&engine.version, v));
+        println!("[UPDATE] {}: 已安装={}, 版本={:?}, 最新版本={}", engine.id, installed, installed_version, engine.version);
+        updates.push(EngineUpdateInfo {
+            id: engine.id,
+            name: engine.name,
+            installed,
+            installed_version,
+            latest_version: engine.version,
+            update_available,
+        });
     }
-    file.flush().map_err(|e| format!("刷新包文件失败: {}", e))?;
-    file.sync_all().map_err(|e| format!("同步包文件失败: {}", e))?;
-    
-    println!("[DOWNLOAD_TRANS] 正在解压包文件...");
-    window.emit("download-progress", DownloadProgressPayload {
-        progress: total_size, total: total_size, status: "extracting".to_string(),
-        target: Some("packages".to_string()),
-    }).unwrap_or(());
-
-    let zip_file = fs::File::open(&packages_path).map_err(|e| format!("无法打开 zip 文件: {}", e))?;
-    let mut archive = zip::ZipArchive::new(zip_file).map_err(|e| format!("无法读取 zip 存档: {}", e))?;
+    Ok(updates)
+}
+
+fn extract_7z(archive_path: &Path, dest: &Path) -> Result<(), AppError> {
+    sevenz_rust::decompress_file(archive_path, dest).map_err(|e| archive_invalid(archive_path, format!("{:?}", e)))
+}
+
+fn extract_zip(archive_path: &Path, dest: &Path) -> Result<(), AppError> {
+    let zip_file = fs::File::open(archive_path)?;
+    let mut archive = zip::ZipArchive::new(zip_file).map_err(|e| archive_invalid(archive_path, e))?;
     for i in 0..archive.len() {
-        let mut file = archive.by_index(i).map_err(|e| format!("无法读取 zip 中的文件: {}", e))?;
+        let mut file = archive.by_index(i).map_err(|e| archive_invalid(archive_path, e))?;
         let outpath = match file.enclosed_name() {
-            Some(path) => local_data_dir.join(path),
+            Some(path) => dest.join(path),
             None => continue,
         };
         if (*file.name()).ends_with('/') {
-            fs::create_dir_all(&outpath).unwrap();
+            fs::create_dir_all(&outpath)?;
         } else {
             if let Some(p) = outpath.parent() {
                 if !p.exists() {
-                    fs::create_dir_all(&p).unwrap();
+                    fs::create_dir_all(p)?;
                 }
             }
-            let mut outfile = fs::File::create(&outpath).map_err(|e| format!("创建解压文件失败: {}", e))?;
-            std::io::copy(&mut file, &mut outfile).map_err(|e| format!("写入解压文件失败: {}", e))?;
+            let mut outfile = fs::File::create(&outpath)?;
+            std::io::copy(&mut file, &mut outfile)?;
         }
     }
-    let _ = fs::remove_file(packages_path);
+    Ok(())
+}
 
-    // 2. 下载 translate_engine_cpu.exe 并重命名
-    println!("[DOWNLOAD_TRANS] 正在下载翻译引擎: {}", TRANSLATOR_EXE_URL);
-    let exe_path = local_data_dir.join(TRANSLATOR_EXE_NAME);
-    let res = client.get(TRANSLATOR_EXE_URL).send().await.map_err(|e| format!("请求引擎文件失败: {}", e))?;
-    let total_size = res.content_length().unwrap_or(0);
-    let mut downloaded: u64 = 0;
-    let mut stream = res.bytes_stream();
-    let mut file = fs::File::create(&exe_path).map_err(|e| format!("创建引擎文件失败: {}", e))?;
+fn archive_invalid(archive_path: &Path, detail: impl std::fmt::Display) -> AppError {
+    AppError::ArchiveInvalid {
+        file: archive_path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+        detail: detail.to_string(),
+    }
+}
 
-    while let Some(item) = stream.next().await {
-        let chunk = item.map_err(|e| format!("下载引擎文件出错: {}", e))?;
-        file.write_all(&chunk).map_err(|e| format!("写入引擎文件失败: {}", e))?;
-        downloaded += chunk.len() as u64;
+// --- 离线安装 ---
+
+/// 从本地的安装包 (.7z / .zip / .exe) 或已解压的文件夹安装引擎，供无法联网的环境使用。
+/// `engine` 为 "ocr" 或 "translator"，进度通过与在线下载相同的事件通知前端。
+#[tauri::command]
+pub async fn install_engine_from_path(app: tauri::AppHandle, engine: String, path: String) -> Result<(), AppError> {
+    // 先确认引擎名称，之后才用它拼接临时目录的路径
+    let is_ocr = match engine.as_str() {
+        "ocr" => true,
+        "translator" => false,
+        other => return Err(AppError::UnknownEngine { engine: other.to_string() }),
+    };
+    let window = main_window(&app)?;
+    let local_data_dir = manifest::local_data_dir(&app)?;
+    fs::create_dir_all(&local_data_dir)?;
+    let source = PathBuf::from(&path);
+    if !source.exists() {
+        return Err(AppError::FileNotFound { path });
+    }
+    println!("[INSTALL] 从本地安装 {}: {:?}", engine, source);
+
+    // 安装包先解压到临时目录，检查目录结构无误后再安装。目录名带时间戳，同时进行的两次安装互不影响
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let staging_dir = local_data_dir.join(format!(".install-{}-{}", engine, timestamp));
+    let result = if is_ocr {
+        install_ocr_from_path(&app, &window, &source, &staging_dir).await
+    } else {
+        install_translator_from_path(&app, &window, &source, &staging_dir).await
+    };
+    let _ = fs::remove_dir_all(&staging_dir);
+    if let Err(e) = &result {
+        eprintln!("[INSTALL] 错误: {}", e);
+    }
+    result
+}
+
+async fn install_ocr_from_path(app: &tauri::AppHandle, window: &tauri::Window, source: &Path, staging_dir: &Path) -> Result<(), AppError> {
+    let emit = |status: &str| {
+        window.emit("ocr-download-progress", DownloadProgressPayload {
+            progress: 0, total: 0, status: status.to_string(),
+            target: Some("ocr".to_string()),
+        }).unwrap_or(());
+    };
+
+    let engine = manifest::engine_spec(app, RAPIDOCR_ENGINE_ID)?;
+    let exe_name = engine.entry_file_name();
+
+    emit("extracting");
+    let root = stage_source(source, staging_dir)?;
+    let engine_dir = find_dir_containing(&root, |name| name == exe_name)
+        .ok_or_else(|| AppError::PackageIncomplete { file: exe_name.to_string() })?;
+
+    // 与在线下载相同，安装到新版本的目录 (离线安装包视为清单中的版本)，已安装的版本保持不变
+    let local_data_dir = manifest::local_data_dir(app)?;
+    let (old_record, new_version) = new_engine_version(app, &engine).await;
+    let install_dir = new_version.install_path(&local_data_dir);
+    // 可执行文件所在的整个目录即为引擎目录
+    let target_dir = new_version.entry_path(&local_data_dir).parent().map(Path::to_path_buf)
+        .ok_or_else(|| AppError::ManifestInvalid { detail: engine.entry.clone() })?;
+    if let Err(e) = copy_dir_all(&engine_dir, &target_dir) {
+        let _ = fs::remove_dir_all(&install_dir);
+        return Err(e.into());
+    }
+    println!("[INSTALL] OCR 引擎已安装到: {:?}", install_dir);
+
+    emit("testing");
+    activate_engine_version(app, &engine, new_version, old_record).await?;
+    emit("completed");
+    Ok(())
+}
+
+async fn install_translator_from_path(app: &tauri::AppHandle, window: &tauri::Window, source: &Path, staging_dir: &Path) -> Result<(), AppError> {
+    let emit = |target: &str, status: &str| {
         window.emit("download-progress", DownloadProgressPayload {
-            progress: downloaded, total: total_size, status: "downloading".to_string(),
-            target: Some("engine".to_string()),
+            progress: 0, total: 0, status: status.to_string(),
+            target: Some(target.to_string()),
         }).unwrap_or(());
+    };
+    let engine = manifest::engine_spec(app, translator::TRANSLATOR_WORKER_NAME)?;
+    // 发布的可执行文件名可能带有变体后缀 (如 translate_engine_cpu.exe)，按入口文件名的主干匹配
+    let entry_name = Path::new(engine.entry_file_name());
+    let entry_stem = entry_name.file_stem().unwrap_or_default().to_string_lossy().to_ascii_lowercase();
+    let entry_ext = entry_name.extension().unwrap_or_default().to_string_lossy().to_ascii_lowercase();
+    let is_engine_exe = |name: &str| {
+        let name = Path::new(name);
+        name.file_stem().is_some_and(|stem| stem.to_string_lossy().to_ascii_lowercase().starts_with(&entry_stem))
+            && name.extension().unwrap_or_default().to_string_lossy().eq_ignore_ascii_case(&entry_ext)
+    };
+    let exe_path = manifest::entry_path(app, translator::TRANSLATOR_WORKER_NAME)?;
+
+    // 单独的可执行文件：只更新翻译引擎，保留已安装的语言包
+    let (engine_exe, packages_dir) = if source.is_file() && is_engine_exe(&source.file_name().unwrap_or_default().to_string_lossy()) {
+        (Some(source.to_path_buf()), None)
+    } else {
+        emit("packages", "extracting");
+        let root = stage_source(source, staging_dir)?;
+        let engine_exe = find_dir_containing(&root, is_engine_exe).and_then(|dir| {
+            fs::read_dir(&dir).ok()?.flatten()
+                .map(|entry| entry.path())
+                .find(|path| path.file_name().is_some_and(|name| is_engine_exe(&name.to_string_lossy())))
+        });
+        let packages_dir = find_dir_containing(&root, |name| name == "packages").map(|dir| dir.join("packages"));
+        if engine_exe.is_none() && packages_dir.is_none() {
+            return Err(AppError::PackageIncomplete { file: format!("{} / packages", engine.entry_file_name()) });
+        }
+        (engine_exe, packages_dir)
+    };
+    // 安装包中缺少的部分从当前已安装的版本复制
+    let engine_exe = engine_exe.unwrap_or_else(|| exe_path.clone());
+    if !engine_exe.exists() {
+        return Err(AppError::PackageIncomplete { file: engine.entry_file_name().to_string() });
+    }
+    let packages_dir = packages_dir.unwrap_or_else(|| exe_path.with_file_name("packages"));
+
+    // 与在线下载相同，安装到新版本的目录 (离线安装包视为清单中的版本)，已安装的版本保持不变
+    let local_data_dir = manifest::local_data_dir(app)?;
+    let (old_record, new_version) = new_engine_version(app, &engine).await;
+    let install_dir = new_version.install_path(&local_data_dir);
+    let new_exe_path = new_version.entry_path(&local_data_dir);
+    let copied = (|| {
+        if packages_dir.is_dir() {
+            copy_dir_all(&packages_dir, &new_exe_path.with_file_name("packages"))?;
+            println!("[INSTALL] 语言包已安装");
+        }
+        emit("packages", "completed");
+
+        emit("engine", "extracting");
+        if let Some(parent) = new_exe_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&engine_exe, &new_exe_path)?;
+        println!("[INSTALL] 翻译引擎已安装: {:?}", new_exe_path);
+        Ok::<_, std::io::Error>(())
+    })();
+    if let Err(e) = copied {
+        let _ = fs::remove_dir_all(&install_dir);
+        return Err(e.into());
     }
-    file.flush().map_err(|e| format!("刷新引擎文件失败: {}", e))?;
-    file.sync_all().map_err(|e| format!("同步引擎文件失败: {}", e))?;
 
-    window.emit("download-progress", DownloadProgressPayload {
-        progress: total_size, total: total_size, status: "completed".to_string(),
-        target: Some("engine".to_string()),
-    }).unwrap_or(());
+    emit("engine", "testing");
+    activate_engine_version(app, &engine, new_version, old_record).await?;
+    emit("engine", "completed");
     Ok(())
 }
 
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case(ext))
+}
+
+/// 准备待安装的文件：文件夹直接使用，安装包解压到 `staging_dir`。返回待查找的根目录。
+fn stage_source(source: &Path, staging_dir: &Path) -> Result<PathBuf, AppError> {
+    if source.is_dir() {
+        return Ok(source.to_path_buf());
+    }
+    let _ = fs::remove_dir_all(staging_dir);
+    fs::create_dir_all(staging_dir)?;
+    if has_extension(source, "7z") {
+        extract_7z(source, staging_dir)?;
+    } else if has_extension(source, "zip") {
+        extract_zip(source, staging_dir)?;
+    } else {
+        return Err(AppError::UnsupportedPackage);
+    }
+    Ok(staging_dir.to_path_buf())
+}
+
+/// 在根目录及其下一级子目录中查找包含指定文件 (或文件夹) 的目录，兼容安装包带有一层顶级文件夹的情况。
+fn find_dir_containing(root: &Path, matches: impl Fn(&str) -> bool) -> Option<PathBuf> {
+    let contains = |dir: &Path| {
+        fs::read_dir(dir).map(|entries| {
+            entries.flatten().any(|entry| matches(&entry.file_name().to_string_lossy()))
+        }).unwrap_or(false)
+    };
+    if contains(root) {
+        return Some(root.to_path_buf());
+    }
+    fs::read_dir(root).ok()?.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .find(|path| contains(path))
+}
+
+fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+fn main_window(app: &tauri::AppHandle) -> Result<tauri::Window, AppError> {
+    app.get_window("main").ok_or_else(|| AppError::WindowMissing { label: "main".to_string() })
+}
+
+/// 下载文件到 `part_path` (`<目标文件>.part`)，返回文件总大小。
+///
+/// 已存在的 .part 文件通过 Range 请求从断点续传，进度从已下载的部分开始计算。
+/// 网络中断等暂时性错误按指数退避重试；重试用尽后保留 .part 文件，下次下载时继续。
+/// `expected_size` 为清单登记的文件大小，用于判断服务端拒绝续传时 .part 文件是否已经完整。
+async fn download_resumable(
+    client: &reqwest::Client,
+    window: &tauri::Window,
+    url: &str,
+    part_path: &Path,
+    expected_size: Option<u64>,
+    event: &str,
+    target: &str,
+) -> Result<u64, AppError> {
+    let emit = |progress: u64, total: u64, status: &str| {
+        window.emit(event, DownloadProgressPayload {
+            progress, total, status: status.to_string(),
+            target: Some(target.to_string()),
+        }).unwrap_or(());
+    };
+
+    let mut failures = 0;
+    loop {
+        let downloaded = fs::metadata(part_path).map(|m| m.len()).unwrap_or(0);
+        match download_attempt(client, url, part_path, downloaded, expected_size, &emit).await {
+            Ok(total) => return Ok(total),
+            Err(DownloadError::Fatal(e)) => return Err(e),
+            Err(DownloadError::Discard) => {
+                println!("[DOWNLOAD] 已下载的部分与服务端的文件不一致，删除后重新下载: {}", url);
+                fs::remove_file(part_path)?;
+            }
+            Err(DownloadError::Transient(e)) => {
+                // 本次尝试有新的进展时重新计算重试次数
+                if fs::metadata(part_path).map(|m| m.len()).unwrap_or(0) > downloaded {
+                    failures = 0;
+                }
+                failures += 1;
+                if failures > DOWNLOAD_MAX_RETRIES {
+                    return Err(AppError::DownloadFailed { retries: DOWNLOAD_MAX_RETRIES, error: Box::new(e) });
+                }
+                let delay = DOWNLOAD_RETRY_BASE_DELAY * 2u32.pow(failures - 1);
+                println!("[DOWNLOAD] {}，{} 秒后第 {} 次重试: {}", e, delay.as_secs(), failures, url);
+                emit(fs::metadata(part_path).map(|m| m.len()).unwrap_or(0), 0, "retrying");
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+enum DownloadError {
+    /// 网络中断、服务端 5xx 等，可以重试
+    Transient(AppError),
+    Fatal(AppError),
+    /// 已下载的 .part 文件与服务端的文件对不上，需要删除后从头下载
+    Discard,
+}
+
+/// 根据服务端对下载请求的响应，决定如何处理已下载的 .part 文件
+#[derive(Debug, PartialEq)]
+enum ResumeAction {
+    /// 从断点处追加
+    Append,
+    /// 服务端返回了完整的文件，覆盖已下载的部分
+    Restart,
+    /// 已下载的部分就是完整的文件
+    Complete,
+    /// 已下载的部分无法确认，删除后从头下载
+    Discard,
+}
+
+/// `downloaded` 为 .part 文件的大小 (大于 0 时请求中带有 Range)，`expected_size` 为清单登记的文件大小。
+/// 返回 `None` 表示该响应不能用于下载。
+fn resume_action(
+    status: reqwest::StatusCode,
+    content_range: Option<&str>,
+    downloaded: u64,
+    expected_size: Option<u64>,
+) -> Option<ResumeAction> {
+    let (start, total) = content_range.map_or((None, None), parse_content_range);
+    match status {
+        // 只有服务端返回的起点与断点一致时才能追加
+        reqwest::StatusCode::PARTIAL_CONTENT if start == Some(downloaded) => Some(ResumeAction::Append),
+        reqwest::StatusCode::PARTIAL_CONTENT if downloaded > 0 => Some(ResumeAction::Discard),
+        // 请求的起点超出了文件末尾: 只有文件总大小 (Content-Range 或清单中登记的大小) 与已下载的大小一致时才算下载完整
+        reqwest::StatusCode::RANGE_NOT_SATISFIABLE if downloaded > 0 => match total.or(expected_size) {
+            Some(total) if total == downloaded => Some(ResumeAction::Complete),
+            _ => Some(ResumeAction::Discard),
+        },
+        reqwest::StatusCode::PARTIAL_CONTENT | reqwest::StatusCode::RANGE_NOT_SATISFIABLE => None,
+        // 服务端不支持 Range，只能从头下载
+        s if s.is_success() => Some(ResumeAction::Restart),
+        _ => None,
+    }
+}
+
+/// 解析 Content-Range 响应头 ("bytes 100-199/200"、"bytes */200")，返回 (起点, 文件总大小)。
+fn parse_content_range(value: &str) -> (Option<u64>, Option<u64>) {
+    let Some(rest) = value.trim().strip_prefix("bytes ") else { return (None, None) };
+    let (range, total) = rest.split_once('/').unwrap_or((rest, "*"));
+    let start = range.split_once('-').and_then(|(start, _)| start.trim().parse().ok());
+    (start, total.trim().parse().ok())
+}
+
+fn download_network_error(detail: impl std::fmt::Display) -> AppError {
+    AppError::Network { service: "download".to_string(), detail: detail.to_string() }
+}
+
+async fn download_attempt(
+    client: &reqwest::Client,
+    url: &str,
+    part_path: &Path,
+    mut downloaded: u64,
+    expected_size: Option<u64>,
+    emit: &impl Fn(u64, u64, &str),
+) -> Result<u64, DownloadError> {
+    let mut request = client.get(url);
+    if downloaded > 0 {
+        println!("[DOWNLOAD] 从 {} bytes 处续传: {}", downloaded, url);
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", downloaded));
+    }
+    let res = request.send().await.map_err(|e| DownloadError::Transient(download_network_error(e)))?;
+
+    let status = res.status();
+    let content_range = res.headers().get(reqwest::header::CONTENT_RANGE).and_then(|v| v.to_str().ok());
+    let append = match resume_action(status, content_range, downloaded, expected_size) {
+        Some(ResumeAction::Append) => true,
+        Some(ResumeAction::Restart) => {
+            downloaded = 0;
+            false
+        }
+        Some(ResumeAction::Complete) => return Ok(downloaded),
+        Some(ResumeAction::Discard) => return Err(DownloadError::Discard),
+        None if status.is_server_error() => return Err(DownloadError::Transient(download_network_error(status))),
+        None => return Err(DownloadError::Fatal(download_network_error(status))),
+    };
+    let total_size = res.content_length().map(|len| len + downloaded).unwrap_or(0);
+    println!("[DOWNLOAD] 文件总大小: {} bytes", total_size);
+
+    let mut file = if append {
+        fs::OpenOptions::new().create(true).append(true).open(part_path)
+    } else {
+        fs::File::create(part_path)
+    }.map_err(|e| DownloadError::Fatal(e.into()))?;
+    emit(downloaded, total_size, "downloading");
+
+    let mut stream = res.bytes_stream();
+    while let Some(item) = stream.next().await {
+        let chunk = item.map_err(|e| DownloadError::Transient(download_network_error(e)))?;
+        file.write_all(&chunk).map_err(|e| DownloadError::Fatal(e.into()))?;
+        downloaded += chunk.len() as u64;
+        emit(downloaded, total_size, "downloading");
+    }
+    file.flush().map_err(|e| DownloadError::Fatal(e.into()))?;
+    file.sync_all().map_err(|e| DownloadError::Fatal(e.into()))?;
+
+    if total_size > 0 && downloaded < total_size {
+        return Err(DownloadError::Transient(AppError::DownloadIncomplete { received: downloaded, expected: total_size }));
+    }
+    Ok(downloaded)
+}
+
+/// 校验下载的文件，失败时删除该文件，避免损坏或被篡改的文件被解压或执行。
+async fn verify_artifact(path: &Path, artifact: &Artifact) -> Result<(), AppError> {
+    let result = check_artifact(path, artifact).await;
+    if let Err(e) = &result {
+        eprintln!("[DOWNLOAD] 错误: {}", e);
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+async fn check_artifact(path: &Path, artifact: &Artifact) -> Result<(), AppError> {
+    let file_name = artifact.url_file_name();
+    let actual_size = fs::metadata(path)?.len();
+    if let Some(expected) = artifact.size {
+        if actual_size != expected {
+            return Err(AppError::SizeMismatch { file: file_name.to_string(), expected, actual: actual_size });
+        }
+    }
+
+    // 未登记校验值的文件无法确认来源，不予安装
+    let expected = artifact.sha256.as_deref()
+        .ok_or_else(|| AppError::ChecksumMissing { file: file_name.to_string() })?;
+    // 计算大文件的哈希较慢，放到阻塞线程池中执行，避免占用异步运行时的工作线程
+    let hash_path = path.to_path_buf();
+    let actual_sha256 = tokio::task::spawn_blocking(move || sha256_file(&hash_path))
+        .await
+        .map_err(|e| AppError::Io { detail: e.to_string() })??;
+    if !expected.eq_ignore_ascii_case(&actual_sha256) {
+        return Err(AppError::ChecksumMismatch {
+            file: file_name.to_string(),
+            expected: expected.to_string(),
+            actual: actual_sha256,
+        });
+    }
+    println!("[DOWNLOAD] {} 校验通过 (SHA-256: {})", file_name, actual_sha256);
+    Ok(())
+}
+
+fn sha256_file(path: &Path) -> Result<String, AppError> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
 // --- 核心功能命令 ---
 
 // 处理用户取消截图的命令
@@ -254,7 +771,7 @@ pub async fn process_screenshot_area(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
     x: f64, y: f64, width: f64, height: f64,
-) -> Result<(), String> {
+) -> Result<u64, AppError> {
     println!("[COMMANDS] 处理截图区域: x={}, y={}, w={}, h={}", x, y, width, height);
 
     if let Some(loading_window) = app.get_window("loading") {
@@ -262,9 +779,10 @@ pub async fn process_screenshot_area(
         let _ = loading_window.show();
     }
 
-    let fullscreen_image = {
-        let mut capture_cache = state.fullscreen_capture.lock().unwrap();
-        capture_cache.take().ok_or("错误：在 AppState 中未找到缓存的全屏截图。")?
+    let fullscreen_image = state.fullscreen_capture.lock().unwrap().take();
+    let Some(fullscreen_image) = fullscreen_image else {
+        hide_loading_and_release_lock(&app);
+        return Err(AppError::ScreenshotMissing);
     };
 
     let cropped_image_buffer = image::imageops::crop_imm(
@@ -274,7 +792,8 @@ pub async fn process_screenshot_area(
     let settings = state.settings.lock().unwrap().clone();
     let app_for_task = app.clone();
 
-    tokio::spawn(async move {
+    // 截图锁与加载窗口由任务结束时的回调统一释放，任务被取消或 panic 时也不会遗漏
+    let job_id = jobs::spawn_job(&app, "screenshot", move |job_id| async move {
         let temp_dir = app_for_task.path_resolver().app_cache_dir().unwrap().join("tmp");
         let _ = tokio::fs::create_dir_all(&temp_dir).await;
 
@@ -287,7 +806,6 @@ pub async fn process_screenshot_area(
 
         if let Err(e) = cropped_image_buffer.save(&image_path) {
             eprintln!("[COMMANDS] 保存截图失败: {}", e);
-            hide_loading_and_release_lock(&app_for_task);
             return;
         }
 
@@ -296,17 +814,15 @@ pub async fn process_screenshot_area(
         add_image_to_history(&app_for_task.state(), image_path.clone());
 
         match settings.primary_action.as_str() {
-            "ocr" => handle_ocr_mode(&app_for_task, &image_path_str, &settings, false).await,
-            "ocr_translate" => handle_ocr_mode(&app_for_task, &image_path_str, &settings, true).await,
+            "ocr" => handle_ocr_mode(&app_for_task, job_id, &image_path_str, &settings, false, false).await,
+            "ocr_translate" => handle_ocr_mode(&app_for_task, job_id, &image_path_str, &settings, true, false).await,
             "copy" => handle_copy_mode(&app_for_task, image_path_str).await,
             "save" => handle_save_mode(&app_for_task, image_path_str).await,
             "preview" | _ => handle_preview_mode(&app_for_task, &image_path, image_path_str).await,
         }
+    }, |app, _, _| hide_loading_and_release_lock(app));
 
-        hide_loading_and_release_lock(&app_for_task);
-    });
-
-    Ok(())
+    Ok(job_id)
 }
 
 #[tauri::command]
@@ -315,7 +831,7 @@ pub async fn process_image_from_path(
     state: State<'_, AppState>,
     path: String,
     action: String
-) -> Result<(), String> {
+) -> Result<u64, AppError> {
     println!("[COMMANDS] 手动处理图片: {}, 动作: {}", path, action);
 
     // --- 修改：在开始处理时显示 Loading 窗口 ---
@@ -335,23 +851,42 @@ pub async fn process_image_from_path(
             if let Some(loading_window) = app.get_window("loading") {
                 let _ = loading_window.hide();
             }
-            return Ok(());
+            return Err(AppError::UnknownAction { action });
         }
     };
 
-    handle_ocr_mode(&app, &path, &settings, do_translate).await;
+    let app_for_task = app.clone();
+    let job_id = jobs::spawn_job(&app, "image", move |job_id| async move {
+        handle_ocr_mode(&app_for_task, job_id, &path, &settings, do_translate, true).await;
+
+        let app_handle_for_main_thread = app_for_task.clone();
+        let shown = app_for_task.run_on_main_thread(move || {
+            // --- 修改：在显示结果窗口前，隐藏 Loading 窗口 ---
+            if let Some(loading_window) = app_handle_for_main_thread.get_window("loading") {
+                let _ = loading_window.hide();
+            }
 
-    let app_handle_for_main_thread = app.clone();
-    app.run_on_main_thread(move || {
-        // --- 修改：在显示结果窗口前，隐藏 Loading 窗口 ---
-        if let Some(loading_window) = app_handle_for_main_thread.get_window("loading") {
+            crate::show_results_window_with_cache(&app_handle_for_main_thread);
+        });
+        if let Err(e) = shown {
+            eprintln!("[COMMANDS] 无法在主线程上运行任务: {}", e);
+        }
+    }, |app, job_id, completed| {
+        if let Some(loading_window) = app.get_window("loading") {
             let _ = loading_window.hide();
         }
+        // 结果窗口可能已显示识别结果并在等待译文，通知其翻译已中止
+        if !completed {
+            let _ = app.emit_all("translation_update", TranslationUpdatePayload {
+                job_id,
+                translated_text: None,
+                error: Some(AppError::Cancelled),
+                translator_backend: None,
+            });
+        }
+    });
 
-        crate::show_results_window_with_cache(&app_handle_for_main_thread);
-    }).map_err(|e| format!("无法在主线程上运行任务: {}", e))?;
-
-    Ok(())
+    Ok(job_id)
 }
 
 // --- 辅助函数 ---
@@ -396,7 +931,7 @@ pub fn handle_external_image_open(app: &tauri::AppHandle, external_path: &Path)
         // 3. 将外部图片复制到缓存目录
         if let Err(e) = fs::copy(&path_buf, &dest_path) {
             eprintln!("[THREAD] 错误: 复制外部图片失败: {}", e);
-            send_notification(&app_handle, "❌ 打开失败", &format!("无法复制文件: {}", e));
+            send_notification(&app_handle, &t("notify.open_failed.title"), &tf("notify.open_failed.copy", &[("error", &e)]));
             return;
         }
         println!("[THREAD] 外部图片已成功复制到: {:?}", dest_path);
@@ -418,7 +953,7 @@ pub fn handle_external_image_open(app: &tauri::AppHandle, external_path: &Path)
             },
             Err(e) => {
                 eprintln!("[THREAD] 错误: 无法读取复制后的图片文件: {}", e);
-                send_notification(&app_handle, "❌ 打开失败", "无法读取图片文件以供预览。");
+                send_notification(&app_handle, &t("notify.open_failed.title"), &t("notify.open_failed.read"));
             }
         }
     });
@@ -458,15 +993,15 @@ fn hide_loading_and_release_lock(app: &tauri::AppHandle) {
 
 async fn handle_copy_mode(app: &tauri::AppHandle, path: String) {
     match copy_image_to_clipboard(path).await {
-        Ok(_) => send_notification(app, "✅ 复制成功", "截图已复制到剪贴板。"),
-        Err(e) => send_notification(app, "❌ 复制失败", &e),
+        Ok(_) => send_notification(app, &t("notify.copy_ok.title"), &t("notify.copy_ok.body")),
+        Err(e) => send_notification(app, &t("notify.copy_failed.title"), &e.to_string()),
     }
 }
 
 async fn handle_save_mode(app: &tauri::AppHandle, path: String) {
     match save_image_to_desktop(path).await {
-        Ok(_) => send_notification(app, "✅ 保存成功", "截图已保存到桌面。"),
-        Err(e) => send_notification(app, "❌ 保存失败", &e),
+        Ok(_) => send_notification(app, &t("notify.save_ok.title"), &t("notify.save_ok.body")),
+        Err(e) => send_notification(app, &t("notify.save_failed.title"), &e.to_string()),
     }
 }
 
@@ -479,49 +1014,215 @@ async fn handle_preview_mode(app: &tauri::AppHandle, path: &std::path::Path, pat
         };
         create_and_show_image_viewer_window(app, payload);
     } else {
-        send_notification(app, "❌ 错误", "无法读取截图文件进行预览。");
+        send_notification(app, &t("notify.error.title"), &t("notify.preview_failed.body"));
     }
 }
 
+/// 识别图片文字，并按需翻译。
+///
+/// `stream_to_results` 为 `true` 时，识别完成后立即显示结果窗口，译文随翻译进度逐步填充。
+/// 推送的事件均带有 `job_id`，结果窗口只接收其正在显示的任务的译文。
 async fn handle_ocr_mode(
     app: &tauri::AppHandle,
+    job_id: u64,
     image_path: &str,
     settings: &AppSettings,
-    do_translate: bool
+    do_translate: bool,
+    stream_to_results: bool,
 ) {
-    let ocr_res = perform_ocr(app, image_path, settings);
+    let ocr_res = perform_ocr(app, image_path, settings).await;
     match ocr_res {
-        Ok(text) => {
+        Ok(ocr_result) => {
+            // 根据文本框位置重建分栏与段落
+            let text = layout::reflow(&ocr_result, settings.preserve_line_breaks);
+            println!("[OCR] 识别文本内容: '{}'", text);
             if let Ok(mut clipboard) = arboard::Clipboard::new() {
                 let _ = clipboard.set_text(text.clone());
             }
             if !do_translate {
-                send_notification(app, "✅ 文字识别成功", "内容已复制到剪贴板。");
-                cache_result(app, Some(text), None, image_path.to_string());
+                send_notification(app, &t("notify.ocr_ok.title"), &t("notify.ocr_ok.body"));
+                cache_result(app, job_id, Some(text), None, None, Some(ocr_result), image_path.to_string());
             } else {
-                let translator = translator::get_translator(app);
-                let trans_res = translator.translate(&text, &settings.target_lang).await;
+                let translator = translator::get_translator(app, settings);
+                let pair = translator::resolve_language_pair(&settings.source_lang, &settings.target_lang, &text);
+                if stream_to_results {
+                    cache_result(app, job_id, Some(text.clone()), None, None, Some(ocr_result.clone()), image_path.to_string());
+                    show_results_window(app);
+                }
+                let trans_res = if pair.source == pair.target {
+                    println!("[TRANSLATOR] 源语言与目标语言相同 ('{}')，跳过翻译。", pair.target);
+                    Ok((text.clone(), None))
+                } else {
+                    translate_with_cache(app, job_id, settings, &translator, &text, &pair).await
+                };
                 match trans_res {
-                    Ok(trans_text) => {
+                    Ok((trans_text, backend)) => {
                         if let Ok(mut clipboard) = arboard::Clipboard::new() {
                             let _ = clipboard.set_text(trans_text.clone());
                         }
-                        send_notification(app, "✅ 翻译完成", "译文已复制。按 Win+V 查看原文。");
-                        cache_result(app, Some(text), Some(trans_text), image_path.to_string());
+                        send_notification(app, &t("notify.translate_ok.title"), &t("notify.translate_ok.body"));
+                        let _ = app.emit_all("translation_update", TranslationUpdatePayload {
+                            job_id,
+                            translated_text: Some(trans_text.clone()),
+                            error: None,
+                            translator_backend: backend.clone(),
+                        });
+                        cache_result(app, job_id, Some(text), Some(trans_text), backend, Some(ocr_result), image_path.to_string());
                     },
                     Err(e) => {
-                        let err_msg = if e.contains("找不到翻译引擎") { "未安装翻译引擎，请在设置中下载".to_string() } else { format!("OCR成功但翻译出错: {}", e) };
-                        send_notification(app, "⚠️ 翻译失败", &err_msg);
-                        cache_result(app, Some(text), Some(err_msg), image_path.to_string());
+                        // 配置了备用后端时错误信息包含各后端的失败原因，直接展示
+                        let err_msg = if e.is_engine_missing() { t("notify.translator_missing") } else { tf("notify.translate_failed.body", &[("error", &e)]) };
+                        send_notification(app, &t("notify.translate_failed.title"), &err_msg);
+                        let _ = app.emit_all("translation_update", TranslationUpdatePayload {
+                            job_id,
+                            translated_text: None,
+                            error: Some(e),
+                            translator_backend: None,
+                        });
+                        cache_result(app, job_id, Some(text), Some(err_msg), None, Some(ocr_result), image_path.to_string());
                     }
                 }
             }
         },
         Err(e) => {
-            send_notification(app, "❌ 识别失败", &format!("{}", e));
-            cache_result(app, None, None, image_path.to_string());
+            send_notification(app, &t("notify.ocr_failed.title"), &e.to_string());
+            cache_result(app, job_id, None, None, None, None, image_path.to_string());
+        }
+    }
+}
+
+/// 先查询翻译记忆缓存，未命中时再调用翻译后端并写入缓存。返回 (译文, 完成翻译的后端)。
+///
+/// 术语表中的词在查询缓存与翻译前替换为占位符，得到译文后再还原为指定译法，
+/// 因此缓存中保存的是带占位符的译文，修改术语表后无需清空缓存。
+/// 长文本切分为多个片段分别翻译（可并行），再按原有的段落结构拼接，翻译过程中推送 `translation_progress` 事件。
+async fn translate_with_cache(
+    app: &tauri::AppHandle,
+    job_id: u64,
+    settings: &AppSettings,
+    translator: &FallbackTranslator,
+    text: &str,
+    pair: &LanguagePair,
+) -> Result<(String, Option<String>), AppError> {
+    let state: State<AppState> = app.state();
+    let protected = state.glossary.lock().unwrap().protect(text, pair);
+
+    let chunks = segmenter::split_into_chunks(&protected.text, settings.translation_chunk_size);
+    let parallel = settings.translation_parallel_chunks.max(1);
+    if chunks.len() > 1 {
+        println!("[TRANSLATOR] 文本较长，分为 {} 段翻译 (并行数: {})", chunks.len(), parallel);
+    }
+    let progress = TranslationProgress::new(app, job_id, &chunks, &protected, &pair.target);
+
+    // buffered 在并行执行的同时保持结果与片段的顺序一致
+    let tasks: Vec<_> = chunks.iter().enumerate()
+        .map(|(index, chunk)| translate_chunk(&state, settings, translator, &chunk.text, pair, &progress, index))
+        .collect();
+    let results: Vec<Result<(String, String), AppError>> = futures_util::stream::iter(tasks)
+        .buffered(parallel)
+        .collect()
+        .await;
+
+    let mut translated = Vec::with_capacity(results.len());
+    let mut backend = None;
+    for result in results {
+        let (text, name) = result?;
+        backend.get_or_insert(name);
+        translated.push(text);
+    }
+    let reassembled = segmenter::reassemble(&chunks, &translated, &pair.target);
+    Ok((protected.restore(&reassembled), backend))
+}
+
+/// 翻译单个片段，先查询翻译记忆缓存。返回 (译文, 完成翻译的后端)。
+async fn translate_chunk(
+    state: &AppState,
+    settings: &AppSettings,
+    translator: &FallbackTranslator,
+    text: &str,
+    pair: &LanguagePair,
+    progress: &TranslationProgress<'_>,
+    index: usize,
+) -> Result<(String, String), AppError> {
+    if settings.translation_cache_enabled {
+        let backend_keys: Vec<String> = translator.backend_names().iter()
+            .map(|name| translation_cache::backend_key(settings, name))
+            .collect();
+        let cached = state.translation_cache.lock().unwrap().get(text, pair, &backend_keys);
+        if let Some((translated, backend)) = cached {
+            println!("[CACHE] 命中翻译缓存 (后端: '{}')", backend);
+            progress.update(index, &translated, true);
+            return Ok((translated, backend));
+        }
+    }
+
+    let on_partial = |partial: &str| progress.update(index, partial, false);
+    let (translated, backend) = translator.translate_with_backend(text, pair, &on_partial).await?;
+    progress.update(index, &translated, true);
+    if settings.translation_cache_enabled {
+        state.translation_cache.lock().unwrap()
+            .insert(text, pair, &translation_cache::backend_key(settings, &backend), &backend, &translated, settings.translation_cache_max_entries);
+    }
+    Ok((translated, backend))
+}
+
+/// 汇总各片段的阶段性译文，并向所有窗口推送翻译进度。
+struct TranslationProgress<'a> {
+    app: &'a tauri::AppHandle,
+    job_id: u64,
+    chunks: &'a [segmenter::Chunk],
+    protected: &'a ProtectedText,
+    target_lang: &'a str,
+    // 各片段目前的译文，尚未开始输出的片段为 None
+    partial: Mutex<Vec<Option<String>>>,
+    completed: AtomicUsize,
+}
+
+impl<'a> TranslationProgress<'a> {
+    fn new(app: &'a tauri::AppHandle, job_id: u64, chunks: &'a [segmenter::Chunk], protected: &'a ProtectedText, target_lang: &'a str) -> Self {
+        Self {
+            app,
+            job_id,
+            chunks,
+            protected,
+            target_lang,
+            partial: Mutex::new(vec![None; chunks.len()]),
+            completed: AtomicUsize::new(0),
         }
     }
+
+    /// 更新第 `index` 个片段的译文；`done` 表示该片段已翻译完成。
+    fn update(&self, index: usize, text: &str, done: bool) {
+        let translated_text = {
+            let mut partial = self.partial.lock().unwrap();
+            partial[index] = Some(text.to_string());
+            // 只拼接从第一段开始连续已有输出的片段，避免并行翻译时中间出现空缺
+            let ready: Vec<String> = partial.iter().map_while(|p| p.clone()).collect();
+            self.protected.restore(&segmenter::reassemble(&self.chunks[..ready.len()], &ready, self.target_lang))
+        };
+        let completed = if done {
+            self.completed.fetch_add(1, Ordering::SeqCst) + 1
+        } else {
+            self.completed.load(Ordering::SeqCst)
+        };
+        let _ = self.app.emit_all("translation_progress", TranslationProgressPayload {
+            job_id: self.job_id,
+            translated_text,
+            completed,
+            total: self.chunks.len(),
+        });
+    }
+}
+
+/// 在主线程上隐藏加载窗口并显示结果窗口。
+fn show_results_window(app: &tauri::AppHandle) {
+    let app_handle = app.clone();
+    let _ = app.run_on_main_thread(move || {
+        if let Some(loading_window) = app_handle.get_window("loading") {
+            let _ = loading_window.hide();
+        }
+        crate::show_results_window_with_cache(&app_handle);
+    });
 }
 
 fn release_lock(app: &tauri::AppHandle) {
@@ -529,12 +1230,15 @@ fn release_lock(app: &tauri::AppHandle) {
     state.is_capturing.store(false, Ordering::SeqCst);
 }
 
-fn cache_result(app: &tauri::AppHandle, original: Option<String>, translated: Option<String>, path: String) {
+fn cache_result(app: &tauri::AppHandle, job_id: u64, original: Option<String>, translated: Option<String>, translator_backend: Option<String>, ocr_result: Option<OcrResult>, path: String) {
     let state: State<AppState> = app.state();
     let mut cache = state.last_ocr_result.lock().unwrap();
     *cache = Some(LastOcrResult {
+        job_id,
         original_text: original,
         translated_text: translated,
+        translator_backend,
+        ocr_result,
         image_path: path,
     });
 }
@@ -543,84 +1247,24 @@ fn send_notification(app: &tauri::AppHandle, title: &str, body: &str) {
     let _ = Notification::new(&app.config().tauri.bundle.identifier).title(title).body(body).show();
 }
 
-fn perform_ocr(app: &tauri::AppHandle, image_path_str: &str, settings: &AppSettings) -> Result<String, String> {
+async fn perform_ocr(app: &tauri::AppHandle, image_path_str: &str, settings: &AppSettings) -> Result<OcrResult, AppError> {
     println!("[OCR] 开始执行 OCR 流程...");
     println!("[OCR] 待识别图片路径: {}", image_path_str);
 
-    let ocr_exe_path = app.path_resolver().app_local_data_dir()
-        .ok_or_else(|| "无法获取本地数据目录".to_string())?
-        .join(OCR_DIR_NAME)
-        .join(OCR_EXE_NAME);
-
-    println!("[OCR] 预期的 OCR 执行文件路径: {:?}", ocr_exe_path);
-
-    if !ocr_exe_path.exists() {
-        let err_msg = "未找到OCR引擎，请在设置页面下载。".to_string();
-        println!("[OCR] 错误: {}", err_msg);
-        return Err(err_msg);
-    }
-    println!("[OCR] OCR 执行文件存在, 准备调用.");
-
-    let ocr_dir = ocr_exe_path.parent().ok_or("无法获取OCR目录")?;
-    println!("[OCR] OCR 工作目录: {:?}", ocr_dir);
-
-    #[cfg(windows)] const CREATE_NO_WINDOW: u32 = 0x08000000;
-    let mut command = StdCommand::new(&ocr_exe_path);
-    let arg = format!("--image_path={}", image_path_str);
-    command.args(&[arg.clone()])
-        .current_dir(&ocr_dir)
-        .env("PYTHONIOENCODING", "utf-8")
-        .env("PYTHONUTF8", "1")
-        .env("PYTHONLEGACYWINDOWSSTDIO", "0");
-    #[cfg(windows)] command.creation_flags(CREATE_NO_WINDOW);
-
-    println!("[OCR] 准备执行命令: {:?} with arg: '{}'", ocr_exe_path, arg);
-
-    let ocr_output = command.output().map_err(|e| {
-        let err_msg = format!("执行OCR进程失败: {}", e);
-        println!("[OCR] 错误: {}", err_msg);
-        err_msg
+    let engine = ocr::get_ocr_engine(app, settings)?;
+    let mut result = engine.recognize(image_path_str).await.map_err(|e| {
+        println!("[OCR] 错误: {}", e);
+        e
     })?;
+    // 在复制或翻译之前过滤掉图标、边框等产生的噪声行
+    result.filter_noise(settings);
 
-    println!("[OCR] 进程执行完毕. Status: {:?}", ocr_output.status);
-
-    if !ocr_output.status.success() {
-        let stderr = String::from_utf8_lossy(&ocr_output.stderr).into_owned();
-        let err_msg = format!("OCR进程返回错误: {}", stderr);
-        println!("[OCR] 错误: {}", err_msg);
-        println!("[OCR] Stderr (raw bytes): {:?}", &ocr_output.stderr);
-        return Err(err_msg);
-    }
-
-    let stdout = String::from_utf8_lossy(&ocr_output.stdout).into_owned();
-    println!("[OCR] Stdout (decoded): '{}'", stdout);
-    println!("[OCR] Stdout (raw bytes): {:?}", &ocr_output.stdout);
-
-    let json_start = stdout.lines().find(|line| line.starts_with('{')).unwrap_or("{}");
-    println!("[OCR] 提取到的 JSON 字符串: '{}'", json_start);
-
-    let ocr_value: serde_json::Value = serde_json::from_str(json_start).map_err(|e| {
-        let err_msg = format!("解析OCR结果JSON失败: {}", e);
-        println!("[OCR] 错误: {}", err_msg);
-        err_msg
-    })?;
-    println!("[OCR] 解析到的 JSON 值: {}", serde_json::to_string_pretty(&ocr_value).unwrap_or_default());
-
-    if ocr_value["code"].as_i64().unwrap_or(0) == 100 {
-        let separator = if settings.preserve_line_breaks { "\n" } else { " " };
-        let text = ocr_value["data"].as_array().unwrap_or(&vec![]).iter()
-            .filter_map(|item| item["text"].as_str()).collect::<Vec<_>>().join(separator);
-        if text.trim().is_empty() {
-            println!("[OCR] 警告: 未识别到任何文字.");
-            Err("未识别到文字".to_string())
-        } else {
-            println!("[OCR] 识别成功, 文本内容: '{}'", text);
-            Ok(text)
-        }
+    if result.is_empty() {
+        println!("[OCR] 警告: 未识别到任何文字.");
+        Err(AppError::NoTextFound)
     } else {
-        let err_msg = ocr_value["data"].as_str().unwrap_or("未知OCR错误").to_string();
-        println!("[OCR] 错误: OCR 引擎返回错误码: {}", err_msg);
-        Err(err_msg)
+        println!("[OCR] 识别成功, 共 {} 行", result.blocks.len());
+        Ok(result)
     }
 }
 
@@ -637,7 +1281,7 @@ fn create_and_show_image_viewer_window(app: &tauri::AppHandle, payload: ImageVie
         } else {
             // 如果窗口不存在，则创建它
             let builder = tauri::WindowBuilder::new(&handle_for_closure, "image_viewer", tauri::WindowUrl::App("image_viewer.html".into()))
-                .title("截图预览").decorations(false).transparent(true).resizable(true).skip_taskbar(true).visible(false);
+                .title(t("window.image_viewer")).decorations(false).transparent(true).resizable(true).skip_taskbar(true).visible(false);
             if let Ok(window) = builder.build() {
                 let window_clone = window.clone();
                 // 监听 "tauri://created" 事件，确保在 webview 加载完成后再发送数据
@@ -648,3 +1292,49 @@ fn create_and_show_image_viewer_window(app: &tauri::AppHandle, payload: ImageVie
         }
     }).unwrap_or_else(|e| eprintln!("[UI] 无法在主线程上创建或显示预览窗口: {}", e));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn content_range_is_parsed() {
+        assert_eq!(parse_content_range("bytes 100-199/200"), (Some(100), Some(200)));
+        assert_eq!(parse_content_range("bytes */200"), (None, Some(200)));
+        assert_eq!(parse_content_range("bytes 100-199/*"), (Some(100), None));
+        assert_eq!(parse_content_range("items 0-1/2"), (None, None));
+    }
+
+    #[test]
+    fn partial_content_is_appended_only_at_the_resume_point() {
+        assert_eq!(resume_action(StatusCode::PARTIAL_CONTENT, Some("bytes 100-199/200"), 100, None), Some(ResumeAction::Append));
+        assert_eq!(resume_action(StatusCode::PARTIAL_CONTENT, Some("bytes 50-199/200"), 100, None), Some(ResumeAction::Discard));
+        assert_eq!(resume_action(StatusCode::PARTIAL_CONTENT, None, 100, Some(200)), Some(ResumeAction::Discard));
+    }
+
+    #[test]
+    fn full_response_restarts_the_download() {
+        assert_eq!(resume_action(StatusCode::OK, None, 100, Some(200)), Some(ResumeAction::Restart));
+        assert_eq!(resume_action(StatusCode::OK, None, 0, None), Some(ResumeAction::Restart));
+    }
+
+    #[test]
+    fn range_not_satisfiable_completes_only_when_the_size_matches() {
+        let status = StatusCode::RANGE_NOT_SATISFIABLE;
+        assert_eq!(resume_action(status, Some("bytes */200"), 200, None), Some(ResumeAction::Complete));
+        assert_eq!(resume_action(status, None, 200, Some(200)), Some(ResumeAction::Complete));
+        // Content-Range 优先于清单登记的大小
+        assert_eq!(resume_action(status, Some("bytes */300"), 200, Some(200)), Some(ResumeAction::Discard));
+        assert_eq!(resume_action(status, Some("bytes */200"), 300, None), Some(ResumeAction::Discard));
+        // 无法得知文件总大小时不能认为已下载完整
+        assert_eq!(resume_action(status, None, 200, None), Some(ResumeAction::Discard));
+        assert_eq!(resume_action(status, Some("bytes */200"), 0, None), None);
+    }
+
+    #[test]
+    fn error_statuses_are_not_resumable() {
+        assert_eq!(resume_action(StatusCode::NOT_FOUND, None, 100, None), None);
+        assert_eq!(resume_action(StatusCode::BAD_GATEWAY, None, 0, None), None);
+    }
+}
@@ -0,0 +1,400 @@
+// 文件: src-tauri\src\error.rs
+//
+// 后端统一的错误类型。
+// 序列化给前端时包含错误代码 `code`、参数 `params` 与按当前界面语言生成的提示 `message`，
+// 前端可以按错误代码做不同的处理（例如提示下载引擎），也可以根据参数自行本地化提示文本。
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::json;
+use std::fmt;
+
+use crate::i18n::{self, t, tf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// 引擎未安装。`engine` 为引擎 ID ("translator"、"rapidocr"、"tesseract") 或自定义 OCR 引擎的名称。
+    EngineMissing { engine: String },
+    /// 引擎进程启动失败、异常退出或通信中断。
+    EngineCrashed { engine: String, detail: String },
+    /// 引擎在限定时间内没有响应，进程已被终止。
+    EngineTimeout { engine: String, secs: u64 },
+    /// 引擎正常运行，但返回了错误（例如缺少语言包）。
+    EngineError { engine: String, detail: String },
+    /// 引擎或接口的输出无法解析。`source` 为引擎或服务的 ID。
+    ParseFailure { source: String, detail: String },
+    /// 图片中没有识别到文字。
+    NoTextFound,
+    Io { detail: String },
+    /// 网络请求失败或服务返回了错误状态。`service` 为服务 ID ("llm"、"libretranslate")。
+    Network { service: String, detail: String },
+    /// 任务被用户取消。
+    Cancelled,
+    /// 所有翻译后端均失败，按尝试顺序记录每个后端的错误。
+    AllBackendsFailed { errors: Vec<(String, AppError)> },
+    /// 未找到本次截图缓存的全屏图像。
+    ScreenshotMissing,
+    /// 前端请求了未知的处理动作。
+    UnknownAction { action: String },
+    /// 未知的引擎 ID，或引擎清单中没有该引擎。
+    UnknownEngine { engine: String },
+    /// 翻译引擎没有安装任何语言包。
+    NoLanguagePacks,
+    /// 引擎清单中没有适用于当前平台的安装包。
+    UnsupportedPlatform { engine: String, platform: String },
+    /// 引擎清单内容无效 (格式错误、路径不安全等)。
+    ManifestInvalid { detail: String },
+    /// 下载重试次数用尽，`error` 为最后一次失败的原因。
+    DownloadFailed { retries: u32, error: Box<AppError> },
+    /// 服务器提前关闭连接，文件未下载完整。
+    DownloadIncomplete { received: u64, expected: u64 },
+    /// 下载的文件大小与清单不符，文件已删除。
+    SizeMismatch { file: String, expected: u64, actual: u64 },
+    /// 下载的文件 SHA-256 与清单不符，文件已删除。
+    ChecksumMismatch { file: String, expected: String, actual: String },
+    /// 清单中没有登记该文件的 SHA-256，无法确认文件未被篡改，不予下载安装。
+    ChecksumMissing { file: String },
+    /// 安装包无法解压。
+    ArchiveInvalid { file: String, detail: String },
+    /// 离线安装时选择的文件不存在。
+    FileNotFound { path: String },
+    /// 离线安装时选择的安装包格式不受支持。
+    UnsupportedPackage,
+    /// 离线安装的安装包或文件夹中缺少必需的文件。`file` 为缺少的文件名。
+    PackageIncomplete { file: String },
+    /// 新安装的引擎未通过自检。`restored` 为恢复到的版本 (版本未知时为空字符串)，为 None 表示已撤销安装。
+    SmokeTestFailed { engine: String, version: String, restored: Option<String>, error: Box<AppError> },
+    /// 无法获取应用目录。`dir` 为 "local_data"、"config"、"desktop"。
+    DirUnavailable { dir: String },
+    /// 找不到指定的窗口。
+    WindowMissing { label: String },
+    /// 翻译后端不支持所选的语言组合。
+    UnsupportedPair { source: String, target: String },
+    /// 不支持的界面语言。
+    UnsupportedUiLanguage { lang: String },
+    /// 注册全局快捷键失败。
+    ShortcutFailed { shortcut: String, detail: String },
+    /// 术语表文件第 `line` 行只有两列，但没有指定语言对。
+    GlossaryMissingPair { line: usize },
+    /// 术语表文件第 `line` 行的列数不是 2 或 4。
+    GlossaryBadColumns { line: usize },
+    /// 图片无法读取或解码。
+    ImageInvalid { detail: String },
+    /// 系统剪贴板不可用。
+    Clipboard { detail: String },
+    /// 无法获取显示器列表。
+    MonitorsUnavailable { detail: String },
+    /// 没有找到可以截图的显示器。
+    NoMonitor,
+    /// 在指定的显示器上截图失败。
+    CaptureFailed { monitor: String, detail: String },
+    /// 截图无法编码为 PNG。
+    ImageEncodeFailed { detail: String },
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::EngineMissing { .. } => "engine_missing",
+            AppError::EngineCrashed { .. } => "engine_crashed",
+            AppError::EngineTimeout { .. } => "engine_timeout",
+            AppError::EngineError { .. } => "engine_error",
+            AppError::ParseFailure { .. } => "parse_failure",
+            AppError::NoTextFound => "no_text_found",
+            AppError::Io { .. } => "io",
+            AppError::Network { .. } => "network",
+            AppError::Cancelled => "cancelled",
+            AppError::AllBackendsFailed { .. } => "all_backends_failed",
+            AppError::ScreenshotMissing => "screenshot_missing",
+            AppError::UnknownAction { .. } => "unknown_action",
+            AppError::UnknownEngine { .. } => "unknown_engine",
+            AppError::NoLanguagePacks => "no_language_packs",
+            AppError::UnsupportedPlatform { .. } => "unsupported_platform",
+            AppError::ManifestInvalid { .. } => "manifest_invalid",
+            AppError::DownloadFailed { .. } => "download_failed",
+            AppError::DownloadIncomplete { .. } => "download_incomplete",
+            AppError::SizeMismatch { .. } => "size_mismatch",
+            AppError::ChecksumMismatch { .. } => "checksum_mismatch",
+            AppError::ChecksumMissing { .. } => "checksum_missing",
+            AppError::ArchiveInvalid { .. } => "archive_invalid",
+            AppError::FileNotFound { .. } => "file_not_found",
+            AppError::UnsupportedPackage => "unsupported_package",
+            AppError::PackageIncomplete { .. } => "package_incomplete",
+            AppError::SmokeTestFailed { .. } => "smoke_test_failed",
+            AppError::DirUnavailable { .. } => "dir_unavailable",
+            AppError::WindowMissing { .. } => "window_missing",
+            AppError::UnsupportedPair { .. } => "unsupported_pair",
+            AppError::UnsupportedUiLanguage { .. } => "unsupported_ui_language",
+            AppError::ShortcutFailed { .. } => "shortcut_failed",
+            AppError::GlossaryMissingPair { .. } => "glossary_missing_pair",
+            AppError::GlossaryBadColumns { .. } => "glossary_bad_columns",
+            AppError::ImageInvalid { .. } => "image_invalid",
+            AppError::Clipboard { .. } => "clipboard",
+            AppError::MonitorsUnavailable { .. } => "monitors_unavailable",
+            AppError::NoMonitor => "no_monitor",
+            AppError::CaptureFailed { .. } => "capture_failed",
+            AppError::ImageEncodeFailed { .. } => "image_encode_failed",
+        }
+    }
+
+    pub fn params(&self) -> serde_json::Value {
+        match self {
+            AppError::EngineMissing { engine } => json!({ "engine": engine }),
+            AppError::EngineCrashed { engine, detail } | AppError::EngineError { engine, detail } => {
+                json!({ "engine": engine, "detail": detail })
+            }
+            AppError::EngineTimeout { engine, secs } => json!({ "engine": engine, "secs": secs }),
+            AppError::ParseFailure { source, detail } => json!({ "source": source, "detail": detail }),
+            AppError::Network { service, detail } => json!({ "service": service, "detail": detail }),
+            AppError::Io { detail }
+            | AppError::ManifestInvalid { detail }
+            | AppError::ImageInvalid { detail }
+            | AppError::Clipboard { detail }
+            | AppError::MonitorsUnavailable { detail }
+            | AppError::ImageEncodeFailed { detail } => json!({ "detail": detail }),
+            AppError::CaptureFailed { monitor, detail } => json!({ "monitor": monitor, "detail": detail }),
+            AppError::AllBackendsFailed { errors } => json!({
+                "errors": errors.iter()
+                    .map(|(backend, error)| json!({ "backend": backend, "error": error }))
+                    .collect::<Vec<_>>(),
+            }),
+            AppError::UnknownAction { action } => json!({ "action": action }),
+            AppError::UnknownEngine { engine } => json!({ "engine": engine }),
+            AppError::UnsupportedPlatform { engine, platform } => json!({ "engine": engine, "platform": platform }),
+            AppError::DownloadFailed { retries, error } => json!({ "retries": retries, "error": error }),
+            AppError::DownloadIncomplete { received, expected } => json!({ "received": received, "expected": expected }),
+            AppError::SizeMismatch { file, expected, actual } => json!({ "file": file, "expected": expected, "actual": actual }),
+            AppError::ChecksumMismatch { file, expected, actual } => json!({ "file": file, "expected": expected, "actual": actual }),
+            AppError::ChecksumMissing { file } => json!({ "file": file }),
+            AppError::ArchiveInvalid { file, detail } => json!({ "file": file, "detail": detail }),
+            AppError::FileNotFound { path } => json!({ "path": path }),
+            AppError::PackageIncomplete { file } => json!({ "file": file }),
+            AppError::SmokeTestFailed { engine, version, restored, error } => {
+                json!({ "engine": engine, "version": version, "restored": restored, "error": error })
+            }
+            AppError::DirUnavailable { dir } => json!({ "dir": dir }),
+            AppError::WindowMissing { label } => json!({ "label": label }),
+            AppError::UnsupportedPair { source, target } => json!({ "source": source, "target": target }),
+            AppError::UnsupportedUiLanguage { lang } => json!({ "lang": lang }),
+            AppError::ShortcutFailed { shortcut, detail } => json!({ "shortcut": shortcut, "detail": detail }),
+            AppError::GlossaryMissingPair { line } | AppError::GlossaryBadColumns { line } => json!({ "line": line }),
+            AppError::NoTextFound
+            | AppError::Cancelled
+            | AppError::ScreenshotMissing
+            | AppError::NoLanguagePacks
+            | AppError::UnsupportedPackage
+            | AppError::NoMonitor => json!({}),
+        }
+    }
+
+    /// 是否为"引擎未安装"的错误（包括所有翻译后端都因引擎未安装而失败的情况）。
+    pub fn is_engine_missing(&self) -> bool {
+        match self {
+            AppError::EngineMissing { .. } => true,
+            AppError::AllBackendsFailed { errors } => errors.iter().all(|(_, e)| e.is_engine_missing()),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::EngineMissing { engine } => match engine.as_str() {
+                "translator" | "rapidocr" | "tesseract" => write!(f, "{}", t(&format!("error.engine_missing.{}", engine))),
+                other => write!(f, "{}", tf("error.engine_missing.custom", &[("engine", &other)])),
+            },
+            AppError::EngineCrashed { engine, detail } => {
+                write!(f, "{}", tf("error.engine_crashed", &[("engine", &i18n::display_name(engine)), ("detail", detail)]))
+            }
+            AppError::EngineTimeout { engine, secs } => {
+                write!(f, "{}", tf("error.engine_timeout", &[("engine", &i18n::display_name(engine)), ("secs", secs)]))
+            }
+            AppError::EngineError { engine, detail } => {
+                write!(f, "{}", tf("error.engine_error", &[("engine", &i18n::display_name(engine)), ("detail", detail)]))
+            }
+            AppError::ParseFailure { source, detail } => {
+                write!(f, "{}", tf("error.parse_failure", &[("source", &i18n::display_name(source)), ("detail", detail)]))
+            }
+            AppError::NoTextFound => write!(f, "{}", t("error.no_text_found")),
+            AppError::Io { detail } => write!(f, "{}", tf("error.io", &[("detail", detail)])),
+            AppError::Network { service, detail } => {
+                write!(f, "{}", tf("error.network", &[("service", &i18n::display_name(service)), ("detail", detail)]))
+            }
+            AppError::Cancelled => write!(f, "{}", t("error.cancelled")),
+            AppError::AllBackendsFailed { errors } => {
+                let lines: Vec<String> = errors.iter().map(|(backend, e)| format!("[{}] {}", backend, e)).collect();
+                write!(f, "{}", lines.join("\n"))
+            }
+            AppError::ScreenshotMissing => write!(f, "{}", t("error.screenshot_missing")),
+            AppError::UnknownAction { action } => write!(f, "{}", tf("error.unknown_action", &[("action", action)])),
+            AppError::UnknownEngine { engine } => write!(f, "{}", tf("error.unknown_engine", &[("engine", engine)])),
+            AppError::NoLanguagePacks => write!(f, "{}", t("error.no_language_packs")),
+            AppError::UnsupportedPlatform { engine, platform } => {
+                write!(f, "{}", tf("error.unsupported_platform", &[("engine", &i18n::display_name(engine)), ("platform", platform)]))
+            }
+            AppError::ManifestInvalid { detail } => write!(f, "{}", tf("error.manifest_invalid", &[("detail", detail)])),
+            AppError::DownloadFailed { retries, error } => {
+                write!(f, "{}", tf("error.download_failed", &[("retries", retries), ("error", error)]))
+            }
+            AppError::DownloadIncomplete { received, expected } => {
+                write!(f, "{}", tf("error.download_incomplete", &[("received", received), ("expected", expected)]))
+            }
+            AppError::SizeMismatch { file, expected, actual } => {
+                write!(f, "{}", tf("download.size_mismatch", &[("file", file), ("expected", expected), ("actual", actual)]))
+            }
+            AppError::ChecksumMismatch { file, expected, actual } => {
+                write!(f, "{}", tf("download.checksum_mismatch", &[("file", file), ("expected", expected), ("actual", actual)]))
+            }
+            AppError::ChecksumMissing { file } => write!(f, "{}", tf("download.checksum_missing", &[("file", file)])),
+            AppError::ArchiveInvalid { file, detail } => {
+                write!(f, "{}", tf("error.archive_invalid", &[("file", file), ("detail", detail)]))
+            }
+            AppError::FileNotFound { path } => write!(f, "{}", tf("error.file_not_found", &[("path", path)])),
+            AppError::UnsupportedPackage => write!(f, "{}", t("error.unsupported_package")),
+            AppError::PackageIncomplete { file } => write!(f, "{}", tf("error.package_incomplete", &[("file", file)])),
+            AppError::SmokeTestFailed { engine, version, restored, error } => {
+                let engine = i18n::display_name(engine);
+                let text = match restored.as_deref() {
+                    Some("") => tf("error.smoke_test_failed.restored", &[("engine", &engine), ("version", version), ("error", error)]),
+                    Some(previous) => tf("error.smoke_test_failed.restored_version", &[
+                        ("engine", &engine), ("version", version), ("previous", &previous), ("error", error),
+                    ]),
+                    None => tf("error.smoke_test_failed.reverted", &[("engine", &engine), ("version", version), ("error", error)]),
+                };
+                write!(f, "{}", text)
+            }
+            AppError::DirUnavailable { dir } => write!(f, "{}", t(&format!("error.dir_unavailable.{}", dir))),
+            AppError::WindowMissing { label } => write!(f, "{}", tf("error.window_missing", &[("label", label)])),
+            AppError::UnsupportedPair { source, target } => {
+                write!(f, "{}", tf("settings.unsupported_pair", &[("source", source), ("target", target)]))
+            }
+            AppError::UnsupportedUiLanguage { lang } => write!(f, "{}", tf("settings.unsupported_ui_language", &[("lang", lang)])),
+            AppError::ShortcutFailed { shortcut, detail } => {
+                write!(f, "{}", tf("settings.register_shortcut_failed", &[("shortcut", shortcut), ("detail", detail)]))
+            }
+            AppError::GlossaryMissingPair { line } => write!(f, "{}", tf("error.glossary_missing_pair", &[("line", line)])),
+            AppError::GlossaryBadColumns { line } => write!(f, "{}", tf("error.glossary_bad_columns", &[("line", line)])),
+            AppError::ImageInvalid { detail } => write!(f, "{}", tf("error.image_invalid", &[("detail", detail)])),
+            AppError::Clipboard { detail } => write!(f, "{}", tf("error.clipboard", &[("detail", detail)])),
+            AppError::MonitorsUnavailable { detail } => write!(f, "{}", tf("error.monitors_unavailable", &[("detail", detail)])),
+            AppError::NoMonitor => write!(f, "{}", t("error.no_monitor")),
+            AppError::CaptureFailed { monitor, detail } => {
+                write!(f, "{}", tf("error.capture_failed", &[("monitor", monitor), ("detail", detail)]))
+            }
+            AppError::ImageEncodeFailed { detail } => write!(f, "{}", tf("error.image_encode_failed", &[("detail", detail)])),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("params", &self.params())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io { detail: e.to_string() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_params_and_nested_errors() {
+        let error = AppError::DownloadFailed {
+            retries: 5,
+            error: Box::new(AppError::DownloadIncomplete { received: 10, expected: 20 }),
+        };
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value["code"], "download_failed");
+        assert_eq!(value["params"]["retries"], 5);
+        assert_eq!(value["params"]["error"]["code"], "download_incomplete");
+        assert_eq!(value["params"]["error"]["params"]["expected"], 20);
+        assert!(value["message"].as_str().unwrap().contains("10"));
+    }
+
+    /// 每个变体 (以及按参数选择不同文本的分支) 各取一个样例。新增变体时需要在这里补充。
+    fn samples() -> Vec<AppError> {
+        let s = |value: &str| value.to_string();
+        let smoke_test_failed = |restored: Option<&str>| AppError::SmokeTestFailed {
+            engine: s("translator"),
+            version: s("1.0"),
+            restored: restored.map(str::to_string),
+            error: Box::new(AppError::NoTextFound),
+        };
+        vec![
+            AppError::EngineMissing { engine: s("translator") },
+            AppError::EngineMissing { engine: s("rapidocr") },
+            AppError::EngineMissing { engine: s("tesseract") },
+            AppError::EngineMissing { engine: s("my-ocr") },
+            AppError::EngineCrashed { engine: s("translator"), detail: s("exit 1") },
+            AppError::EngineTimeout { engine: s("rapidocr"), secs: 30 },
+            AppError::EngineError { engine: s("translator"), detail: s("no model") },
+            AppError::ParseFailure { source: s("llm"), detail: s("eof") },
+            AppError::NoTextFound,
+            AppError::Io { detail: s("denied") },
+            AppError::Network { service: s("libretranslate"), detail: s("timeout") },
+            AppError::Cancelled,
+            AppError::AllBackendsFailed { errors: vec![(s("llm"), AppError::Cancelled)] },
+            AppError::ScreenshotMissing,
+            AppError::UnknownAction { action: s("print") },
+            AppError::UnknownEngine { engine: s("x") },
+            AppError::NoLanguagePacks,
+            AppError::UnsupportedPlatform { engine: s("translator"), platform: s("linux-aarch64") },
+            AppError::ManifestInvalid { detail: s("eof") },
+            AppError::DownloadFailed { retries: 5, error: Box::new(AppError::Cancelled) },
+            AppError::DownloadIncomplete { received: 10, expected: 20 },
+            AppError::SizeMismatch { file: s("a.zip"), expected: 2, actual: 1 },
+            AppError::ChecksumMismatch { file: s("a.zip"), expected: s("aa"), actual: s("bb") },
+            AppError::ChecksumMissing { file: s("a.zip") },
+            AppError::ArchiveInvalid { file: s("a.zip"), detail: s("eof") },
+            AppError::FileNotFound { path: s("a.zip") },
+            AppError::UnsupportedPackage,
+            AppError::PackageIncomplete { file: s("a.exe") },
+            smoke_test_failed(Some("")),
+            smoke_test_failed(Some("0.9")),
+            smoke_test_failed(None),
+            AppError::DirUnavailable { dir: s("local_data") },
+            AppError::DirUnavailable { dir: s("config") },
+            AppError::DirUnavailable { dir: s("desktop") },
+            AppError::WindowMissing { label: s("main") },
+            AppError::UnsupportedPair { source: s("en"), target: s("xx") },
+            AppError::UnsupportedUiLanguage { lang: s("xx") },
+            AppError::ShortcutFailed { shortcut: s("F1"), detail: s("taken") },
+            AppError::GlossaryMissingPair { line: 3 },
+            AppError::GlossaryBadColumns { line: 3 },
+            AppError::ImageInvalid { detail: s("eof") },
+            AppError::Clipboard { detail: s("busy") },
+            AppError::MonitorsUnavailable { detail: s("no session") },
+            AppError::NoMonitor,
+            AppError::CaptureFailed { monitor: s("DISPLAY1"), detail: s("denied") },
+            AppError::ImageEncodeFailed { detail: s("eof") },
+        ]
+    }
+
+    #[test]
+    fn every_variant_has_a_catalog_message() {
+        let mut codes = std::collections::HashSet::new();
+        for error in samples() {
+            codes.insert(error.code());
+            let message = error.to_string();
+            // 目录中没有的键原样返回，形如 "error.xxx"
+            let untranslated = message.split(['.', ' ']).next()
+                .is_some_and(|prefix| ["error", "download", "settings", "detail", "name"].contains(&prefix))
+                && message.contains('.');
+            assert!(!untranslated, "{:?} -> {}", error, message);
+            assert!(!message.contains('{'), "{:?} 存在未替换的占位符: {}", error, message);
+        }
+        // 与 `code()` 中的变体数一致，即每个变体都有样例
+        assert_eq!(codes.len(), 39);
+    }
+}
@@ -0,0 +1,324 @@
+// 文件: src-tauri\src\glossary.rs
+//
+// 用户术语表。
+// 翻译前将术语表中的词替换为占位符，避免翻译后端把产品名、专有名词译得前后不一致，
+// 翻译完成后再将占位符还原为指定的译法。术语按语言对区分，可从 CSV/TSV 文件导入。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+use crate::layout::is_cjk;
+use crate::translator::LanguagePair;
+
+pub const GLOSSARY_FILE_NAME: &str = "glossary.json";
+
+/// 术语表中的一条记录。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GlossaryEntry {
+    pub source_lang: String,
+    pub target_lang: String,
+    // 原文中的术语
+    pub term: String,
+    // 指定的译法
+    pub translation: String,
+}
+
+#[derive(Default)]
+pub struct Glossary {
+    path: Option<PathBuf>,
+    entries: Vec<GlossaryEntry>,
+}
+
+// 术语占位符的格式为 "__TERM<序号>__"
+const PLACEHOLDER_PREFIX: &str = "__TERM";
+const PLACEHOLDER_SUFFIX: &str = "__";
+
+/// 文本中所有术语占位符的字节区间。长文本分段时不能从占位符中间断开，否则译文中无法还原术语。
+pub fn placeholder_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut pos = 0;
+    while let Some(found) = text[pos..].find(PLACEHOLDER_PREFIX) {
+        let start = pos + found;
+        let digits_start = start + PLACEHOLDER_PREFIX.len();
+        let digits = text[digits_start..].bytes().take_while(u8::is_ascii_digit).count();
+        let end = digits_start + digits + PLACEHOLDER_SUFFIX.len();
+        if digits > 0 && text[digits_start + digits..].starts_with(PLACEHOLDER_SUFFIX) {
+            ranges.push(start..end);
+            pos = end;
+        } else {
+            pos = digits_start;
+        }
+    }
+    ranges
+}
+
+/// 替换为占位符后的文本，以及每个占位符对应的译法。
+pub struct ProtectedText {
+    pub text: String,
+    replacements: Vec<(String, String)>,
+}
+
+impl ProtectedText {
+    /// 将译文中的占位符还原为术语的指定译法。
+    pub fn restore(&self, translated: &str) -> String {
+        let mut out = translated.to_string();
+        for (placeholder, translation) in &self.replacements {
+            out = out.replace(placeholder, translation);
+        }
+        out
+    }
+}
+
+impl Glossary {
+    /// 从术语表文件加载，文件不存在或损坏时使用空术语表。
+    pub fn load(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path).ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { path: Some(path), entries }
+    }
+
+    pub fn entries(&self) -> &[GlossaryEntry] {
+        &self.entries
+    }
+
+    /// 替换全部术语并保存到文件。
+    pub fn set_entries(&mut self, entries: Vec<GlossaryEntry>) -> Result<(), AppError> {
+        self.entries = entries;
+        self.save()
+    }
+
+    /// 从 CSV/TSV 文件导入术语，返回导入的条目数。
+    ///
+    /// 每行为 `术语,译法`，或带语言对的 `源语言,目标语言,术语,译法`。
+    /// 只有两列时使用 `default_pair` 指定的语言对。已存在的同名术语会被覆盖。
+    pub fn import(&mut self, file: &Path, default_pair: Option<&LanguagePair>) -> Result<usize, AppError> {
+        let content = fs::read_to_string(file)?;
+        let content = content.trim_start_matches('\u{feff}'); // Excel 导出的 CSV 带有 BOM
+        let is_tsv = file.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("tsv"))
+            || content.lines().next().is_some_and(|line| line.contains('\t'));
+        let delimiter = if is_tsv { '\t' } else { ',' };
+
+        let mut imported = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = split_row(line, delimiter);
+            let entry = match fields.as_slice() {
+                [term, translation] => {
+                    let pair = default_pair.ok_or(AppError::GlossaryMissingPair { line: index + 1 })?;
+                    GlossaryEntry {
+                        source_lang: pair.source.clone(),
+                        target_lang: pair.target.clone(),
+                        term: term.clone(),
+                        translation: translation.clone(),
+                    }
+                }
+                [source, target, term, translation] => GlossaryEntry {
+                    source_lang: source.clone(),
+                    target_lang: target.clone(),
+                    term: term.clone(),
+                    translation: translation.clone(),
+                },
+                _ => return Err(AppError::GlossaryBadColumns { line: index + 1 }),
+            };
+            // 跳过表头
+            if index == 0 && is_header(&entry.term) {
+                continue;
+            }
+            if !entry.term.is_empty() {
+                imported.push(entry);
+            }
+        }
+
+        let count = imported.len();
+        for entry in imported {
+            self.entries.retain(|e| !(e.source_lang == entry.source_lang && e.target_lang == entry.target_lang && e.term == entry.term));
+            self.entries.push(entry);
+        }
+        self.save()?;
+        println!("[GLOSSARY] 从 {:?} 导入了 {} 条术语", file, count);
+        Ok(count)
+    }
+
+    /// 将文本中属于该语言对的术语替换为占位符。
+    pub fn protect(&self, text: &str, pair: &LanguagePair) -> ProtectedText {
+        let mut terms: Vec<&GlossaryEntry> = self.entries.iter()
+            .filter(|e| e.source_lang == pair.source && e.target_lang == pair.target)
+            .collect();
+        // 优先匹配较长的术语，避免 "Screen" 抢先替换 "Screen Translator" 的一部分
+        terms.sort_by_key(|e| std::cmp::Reverse(e.term.chars().count()));
+
+        let mut protected = ProtectedText { text: text.to_string(), replacements: Vec::new() };
+        for entry in terms {
+            let placeholder = format!("{}{}{}", PLACEHOLDER_PREFIX, protected.replacements.len(), PLACEHOLDER_SUFFIX);
+            let replaced = replace_term(&protected.text, &entry.term, &placeholder);
+            if replaced != protected.text {
+                protected.text = replaced;
+                protected.replacements.push((placeholder, entry.translation.clone()));
+            }
+        }
+        if !protected.replacements.is_empty() {
+            println!("[GLOSSARY] 已保护 {} 个术语", protected.replacements.len());
+        }
+        protected
+    }
+
+    fn save(&self) -> Result<(), AppError> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(&self.entries).map_err(std::io::Error::from)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+fn is_header(term: &str) -> bool {
+    matches!(term.to_lowercase().as_str(), "term" | "source" | "术语" | "原文")
+}
+
+/// 拆分一行 CSV/TSV，支持以双引号包裹、含分隔符的字段（`""` 表示一个双引号）。
+fn split_row(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// 替换文本中出现的术语。拉丁字母术语只匹配完整的单词，不替换 "cat" 在 "category" 中的部分。
+fn replace_term(text: &str, term: &str, placeholder: &str) -> String {
+    if term.is_empty() {
+        return text.to_string();
+    }
+    let needs_boundary = |c: char| c.is_alphanumeric() && !is_cjk(c);
+    let check_start = term.chars().next().is_some_and(needs_boundary);
+    let check_end = term.chars().last().is_some_and(needs_boundary);
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find(term) {
+        let before = rest[..pos].chars().last().or_else(|| out.chars().last());
+        let after = rest[pos + term.len()..].chars().next();
+        let inside_word = (check_start && before.is_some_and(needs_boundary))
+            || (check_end && after.is_some_and(needs_boundary));
+
+        out.push_str(&rest[..pos]);
+        out.push_str(if inside_word { term } else { placeholder });
+        rest = &rest[pos + term.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(source: &str, target: &str, term: &str, translation: &str) -> GlossaryEntry {
+        GlossaryEntry {
+            source_lang: source.to_string(),
+            target_lang: target.to_string(),
+            term: term.to_string(),
+            translation: translation.to_string(),
+        }
+    }
+
+    fn write_temp_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("screentranslator-glossary-test-{}-{}", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn replace_term_matches_whole_latin_words_only() {
+        assert_eq!(replace_term("cat category cat.", "cat", "#"), "# category #.");
+        assert_eq!(replace_term("bobcat cat", "cat", "#"), "bobcat #");
+        assert_eq!(replace_term("Hello", "", "#"), "Hello");
+    }
+
+    #[test]
+    fn replace_term_ignores_word_boundaries_for_cjk() {
+        assert_eq!(replace_term("打开截图翻译工具", "截图翻译", "#"), "打开#工具");
+        assert_eq!(replace_term("Use C++ now", "C++", "#"), "Use # now");
+    }
+
+    #[test]
+    fn split_row_handles_quotes_and_delimiters() {
+        assert_eq!(split_row("a, b ,c", ','), ["a", "b", "c"]);
+        assert_eq!(split_row("\"Hello, world\",你好", ','), ["Hello, world", "你好"]);
+        assert_eq!(split_row("\"say \"\"hi\"\"\",打招呼", ','), ["say \"hi\"", "打招呼"]);
+        assert_eq!(split_row("a,b\tc", '\t'), ["a,b", "c"]);
+        assert_eq!(split_row("term,", ','), ["term", ""]);
+    }
+
+    #[test]
+    fn placeholder_ranges_find_only_complete_tokens() {
+        let text = "__TERM0__ and __TERM12__, not __TERM__ or __TERMx__";
+        let tokens: Vec<&str> = placeholder_ranges(text).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(tokens, ["__TERM0__", "__TERM12__"]);
+    }
+
+    #[test]
+    fn protect_prefers_longer_terms_and_restores_translations() {
+        let mut glossary = Glossary::default();
+        glossary.set_entries(vec![
+            entry("en", "zh", "Screen", "屏幕"),
+            entry("en", "zh", "Screen Translator", "屏幕翻译器"),
+            entry("en", "ja", "Screen", "スクリーン"),
+        ]).unwrap();
+
+        let protected = glossary.protect("Screen Translator shows the Screen.", &LanguagePair::new("en", "zh"));
+        assert_eq!(protected.text, "__TERM0__ shows the __TERM1__.");
+        assert_eq!(protected.restore("__TERM0__ 显示 __TERM1__。"), "屏幕翻译器 显示 屏幕。");
+    }
+
+    #[test]
+    fn import_reads_csv_with_bom_header_and_language_pairs() {
+        let path = write_temp_file("import.csv", "\u{feff}source,target,term,translation\n# 注释\nen,zh,\"Hello, world\",你好世界\n\nen,ja,cat,猫\n");
+        let mut glossary = Glossary::default();
+        assert_eq!(glossary.import(&path, None), Ok(2));
+        assert_eq!(glossary.entries(), [entry("en", "zh", "Hello, world", "你好世界"), entry("en", "ja", "cat", "猫")]);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn import_uses_default_pair_for_tsv_and_overwrites_existing_terms() {
+        let path = write_temp_file("import.tsv", "cat\t猫咪\ndog\t狗\n");
+        let mut glossary = Glossary::default();
+        glossary.set_entries(vec![entry("en", "zh", "cat", "猫")]).unwrap();
+        assert_eq!(glossary.import(&path, Some(&LanguagePair::new("en", "zh"))), Ok(2));
+        assert_eq!(glossary.entries(), [entry("en", "zh", "cat", "猫咪"), entry("en", "zh", "dog", "狗")]);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn import_rejects_rows_without_language_pair_or_with_wrong_columns() {
+        let two_columns = write_temp_file("no-pair.csv", "cat,猫\n");
+        let three_columns = write_temp_file("bad-columns.csv", "en,zh,cat\n");
+        let mut glossary = Glossary::default();
+        assert!(glossary.import(&two_columns, None).is_err());
+        assert!(glossary.import(&three_columns, Some(&LanguagePair::new("en", "zh"))).is_err());
+        assert!(glossary.entries().is_empty());
+        let _ = fs::remove_file(&two_columns);
+        let _ = fs::remove_file(&three_columns);
+    }
+}
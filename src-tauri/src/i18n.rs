@@ -0,0 +1,179 @@
+// 文件: src-tauri\src\i18n.rs
+//
+// 后端的界面文本目录 (通知、错误提示、窗口标题)。
+// 当前界面语言由设置中的 `ui_language` 决定，启动时与修改设置时通过 `set_language` 更新。
+// 文本中的 `{name}` 占位符由 `tf` 按名称替换。
+
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UiLanguage {
+    ZhCn,
+    EnUs,
+}
+
+impl UiLanguage {
+    /// 无法识别的语言代码按简体中文处理。
+    pub fn from_code(code: &str) -> Self {
+        match code.to_ascii_lowercase().as_str() {
+            "en" | "en-us" => UiLanguage::EnUs,
+            _ => UiLanguage::ZhCn,
+        }
+    }
+}
+
+pub const SUPPORTED_LANGUAGES: &[&str] = &["zh-CN", "en-US"];
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_language(code: &str) {
+    let lang = UiLanguage::from_code(code);
+    CURRENT.store(lang as u8, Ordering::Relaxed);
+    println!("[I18N] 界面语言: {:?}", lang);
+}
+
+pub fn current() -> UiLanguage {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => UiLanguage::EnUs,
+        _ => UiLanguage::ZhCn,
+    }
+}
+
+// (键, 简体中文, English)
+const MESSAGES: &[(&str, &str, &str)] = &[
+    // 窗口标题
+    ("window.settings", "ScreenTranslator 设置", "ScreenTranslator Settings"),
+    ("window.image_viewer", "截图预览", "Screenshot Preview"),
+
+    // 通知
+    ("notify.open_failed.title", "❌ 打开失败", "❌ Failed to open"),
+    ("notify.open_failed.copy", "无法复制文件: {error}", "Could not copy the file: {error}"),
+    ("notify.open_failed.read", "无法读取图片文件以供预览。", "Could not read the image file for preview."),
+    ("notify.copy_ok.title", "✅ 复制成功", "✅ Copied"),
+    ("notify.copy_ok.body", "截图已复制到剪贴板。", "The screenshot has been copied to the clipboard."),
+    ("notify.copy_failed.title", "❌ 复制失败", "❌ Copy failed"),
+    ("notify.save_ok.title", "✅ 保存成功", "✅ Saved"),
+    ("notify.save_ok.body", "截图已保存到桌面。", "The screenshot has been saved to the desktop."),
+    ("notify.save_failed.title", "❌ 保存失败", "❌ Save failed"),
+    ("notify.error.title", "❌ 错误", "❌ Error"),
+    ("notify.preview_failed.body", "无法读取截图文件进行预览。", "Could not read the screenshot file for preview."),
+    ("notify.ocr_ok.title", "✅ 文字识别成功", "✅ Text recognized"),
+    ("notify.ocr_ok.body", "内容已复制到剪贴板。", "The text has been copied to the clipboard."),
+    ("notify.translate_ok.title", "✅ 翻译完成", "✅ Translation complete"),
+    ("notify.translate_ok.body", "译文已复制。按 Win+V 查看原文。", "The translation has been copied. Press Win+V to see the original text."),
+    ("notify.translate_failed.title", "⚠️ 翻译失败", "⚠️ Translation failed"),
+    ("notify.translate_failed.body", "OCR成功但翻译出错: {error}", "Text was recognized but translation failed: {error}"),
+    ("notify.translator_missing", "未安装翻译引擎，请在设置中下载", "The translation engine is not installed. Download it in Settings."),
+    ("notify.ocr_failed.title", "❌ 识别失败", "❌ Recognition failed"),
+
+    // 引擎与服务名称
+    ("name.translator", "翻译引擎", "Translation engine"),
+    ("name.rapidocr", "RapidOCR", "RapidOCR"),
+    ("name.tesseract", "Tesseract", "Tesseract"),
+    ("name.rapidocr_builtin", "RapidOCR (内置)", "RapidOCR (built-in)"),
+    ("name.llm", "大模型接口", "the LLM API"),
+    ("name.libretranslate", "LibreTranslate 服务", "the LibreTranslate service"),
+    ("name.download", "下载服务器", "the download server"),
+    ("name.manifest", "引擎清单镜像", "the engine manifest mirror"),
+
+    // 错误
+    ("error.engine_missing.translator", "找不到翻译引擎，请在设置页面下载安装。", "The translation engine was not found. Download and install it in Settings."),
+    ("error.engine_missing.rapidocr", "未找到OCR引擎，请在设置页面下载。", "The OCR engine was not found. Download it in Settings."),
+    ("error.engine_missing.tesseract", "找不到 Tesseract，请确认已安装并配置路径。", "Tesseract was not found. Make sure it is installed and its path is configured."),
+    ("error.engine_missing.custom", "找不到OCR引擎 '{engine}'", "OCR engine '{engine}' was not found"),
+    ("error.engine_crashed", "{engine}运行失败: {detail}", "{engine} failed to run: {detail}"),
+    ("error.engine_timeout", "{engine}超过 {secs} 秒未响应，已终止", "{engine} did not respond within {secs} seconds and was stopped"),
+    ("error.engine_error", "{engine}返回错误: {detail}", "{engine} returned an error: {detail}"),
+    ("error.parse_failure", "解析{source}的输出失败: {detail}", "Failed to parse the output of {source}: {detail}"),
+    ("error.no_text_found", "未识别到文字", "No text was recognized"),
+    ("error.io", "文件读写失败: {detail}", "File access failed: {detail}"),
+    ("error.network", "请求{service}失败: {detail}", "Request to {service} failed: {detail}"),
+    ("error.cancelled", "任务已取消", "The task was cancelled"),
+    ("error.screenshot_missing", "找不到本次截图的全屏图像，请重新截图", "The captured screen image was not found. Please take the screenshot again"),
+    ("error.unknown_action", "未知动作: '{action}'", "Unknown action: '{action}'"),
+    ("error.unknown_engine", "未知的引擎: {engine}", "Unknown engine: {engine}"),
+    ("error.no_language_packs", "没有可用的语言包", "No language packs are installed"),
+    ("error.unsupported_platform", "{engine}没有适用于当前平台 ({platform}) 的安装包", "{engine} has no package for this platform ({platform})"),
+    ("error.manifest_invalid", "引擎清单无效: {detail}", "The engine manifest is invalid: {detail}"),
+    ("error.download_failed", "下载失败 (已重试 {retries} 次): {error}", "Download failed after {retries} retries: {error}"),
+    ("error.download_incomplete", "连接提前关闭 ({received} / {expected} 字节)", "The connection closed early ({received} / {expected} bytes)"),
+    ("error.archive_invalid", "解压 {file} 失败: {detail}", "Failed to extract {file}: {detail}"),
+    ("error.file_not_found", "找不到文件: {path}", "File not found: {path}"),
+    ("error.unsupported_package", "不支持的安装包格式，请选择 .7z、.zip 或 .exe 文件，或已解压的文件夹", "Unsupported package format. Choose a .7z, .zip or .exe file, or an extracted folder"),
+    ("error.package_incomplete", "所选的安装包或文件夹中没有找到 {file}", "{file} was not found in the selected package or folder"),
+    ("error.smoke_test_failed.restored_version", "{engine} {version} 无法正常运行，已恢复到之前的版本 {previous}: {error}", "{engine} {version} does not work and version {previous} has been restored: {error}"),
+    ("error.smoke_test_failed.restored", "{engine} {version} 无法正常运行，已恢复到之前的版本: {error}", "{engine} {version} does not work and the previous version has been restored: {error}"),
+    ("error.smoke_test_failed.reverted", "{engine} {version} 无法正常运行，已撤销安装: {error}", "{engine} {version} does not work and the installation has been reverted: {error}"),
+    ("error.dir_unavailable.local_data", "无法获取本地数据目录", "The local data directory is unavailable"),
+    ("error.dir_unavailable.config", "无法获取应用配置目录", "The app configuration directory is unavailable"),
+    ("error.dir_unavailable.desktop", "无法获取桌面路径", "The desktop folder is unavailable"),
+    ("error.window_missing", "找不到窗口 '{label}'", "Window '{label}' was not found"),
+    ("error.glossary_missing_pair", "第 {line} 行缺少语言对，请使用 4 列格式或先选择源语言", "Line {line} has no language pair. Use the 4-column format or choose a source language first"),
+    ("error.glossary_bad_columns", "第 {line} 行格式错误，应为 2 列或 4 列", "Line {line} is malformed: expected 2 or 4 columns"),
+    ("error.image_invalid", "无法读取图片: {detail}", "Could not read the image: {detail}"),
+    ("error.clipboard", "无法访问剪贴板: {detail}", "Could not access the clipboard: {detail}"),
+    ("error.monitors_unavailable", "无法获取显示器列表: {detail}", "Could not list the displays: {detail}"),
+    ("error.no_monitor", "未找到任何显示器", "No display was found"),
+    ("error.capture_failed", "在显示器 '{monitor}' 上截图失败: {detail}", "Failed to capture display '{monitor}': {detail}"),
+    ("error.image_encode_failed", "截图编码为 PNG 失败: {detail}", "Failed to encode the screenshot as PNG: {detail}"),
+    ("name.unknown_monitor", "未知名称", "unknown"),
+
+    // 错误详情
+    ("detail.unknown_error", "未知错误", "unknown error"),
+    ("detail.empty_translation", "未返回译文", "no translation was returned"),
+    ("detail.raw_output", "{error}. 原始输出: {output}", "{error}. Raw output: {output}"),
+    ("detail.api_error", "接口返回错误 ({status}): {message}", "the service returned an error ({status}): {message}"),
+    ("detail.stream_read_failed", "读取流式响应失败: {error}", "failed to read the streaming response: {error}"),
+    ("detail.languages_fetch_failed", "获取语言列表失败: {error}", "failed to fetch the language list: {error}"),
+    ("detail.languages_invalid", "语言列表格式错误: {error}", "the language list is malformed: {error}"),
+    ("detail.worker_io_failed", "引擎进程通信失败: {error}", "communication with the engine process failed: {error}"),
+    ("detail.engine_dir_missing", "无法获取引擎目录", "the engine directory could not be determined"),
+    ("detail.spawn_failed", "启动进程失败: {error}", "failed to start the process: {error}"),
+    ("detail.stdin_unavailable", "无法获取引擎进程的标准输入", "the engine process has no standard input"),
+    ("detail.stdout_unavailable", "无法获取引擎进程的标准输出", "the engine process has no standard output"),
+    ("detail.init_failed", "引擎初始化失败: {error}", "engine initialization failed: {error}"),
+    ("detail.write_failed", "写入请求失败: {error}", "failed to send the request: {error}"),
+    ("detail.read_failed", "读取输出失败: {error}", "failed to read the output: {error}"),
+    ("detail.process_exited", "引擎进程已退出", "the engine process exited"),
+
+    ("detail.manifest_unsafe_path", "引擎 {engine} 的 {field} 不是安全的相对路径: {value}", "The {field} of engine {engine} is not a safe relative path: {value}"),
+    ("detail.manifest_missing_checksum", "引擎 {engine} 的安装包 {artifact} 没有登记 SHA-256", "Artifact {artifact} of engine {engine} has no SHA-256"),
+
+    // 引擎下载
+    ("download.size_mismatch", "{file} 大小不符 (应为 {expected} 字节，实际为 {actual} 字节)，文件可能已损坏，已删除", "{file} has the wrong size (expected {expected} bytes, got {actual}). The file may be corrupted and has been deleted"),
+    ("download.checksum_missing", "引擎清单中没有登记 {file} 的校验值，无法确认文件完整，不予安装", "The engine manifest has no checksum for {file}, so its integrity cannot be verified and it will not be installed"),
+    ("download.checksum_mismatch", "{file} 校验失败 (SHA-256 应为 {expected}，实际为 {actual})，文件可能已损坏或被篡改，已删除", "{file} failed verification (expected SHA-256 {expected}, got {actual}). The file may be corrupted or tampered with and has been deleted"),
+
+    // 设置
+    ("settings.unsupported_pair", "当前翻译引擎不支持该语言组合: {source} -> {target}", "The current translator does not support this language pair: {source} -> {target}"),
+    ("settings.register_shortcut_failed", "注册快捷键 {shortcut} 失败: {detail}", "Failed to register the shortcut {shortcut}: {detail}"),
+    ("settings.unsupported_ui_language", "不支持的界面语言: {lang}", "Unsupported UI language: {lang}"),
+];
+
+fn lookup(key: &str) -> Option<&'static str> {
+    let (_, zh, en) = MESSAGES.iter().find(|(k, _, _)| *k == key)?;
+    Some(match current() {
+        UiLanguage::ZhCn => zh,
+        UiLanguage::EnUs => en,
+    })
+}
+
+/// 按当前界面语言取出文本。目录中没有该键时原样返回键名，便于发现遗漏。
+pub fn t(key: &str) -> String {
+    lookup(key).unwrap_or(key).to_string()
+}
+
+/// 取出文本并替换其中的 `{name}` 占位符。
+pub fn tf(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = t(key);
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+/// 引擎或服务 ID 的显示名称。目录中没有时（如自定义 OCR 引擎）直接显示 ID。
+pub fn display_name(id: &str) -> String {
+    lookup(&format!("name.{}", id)).map(str::to_string).unwrap_or_else(|| id.to_string())
+}
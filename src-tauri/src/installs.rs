@@ -0,0 +1,223 @@
+// 文件: src-tauri\src\installs.rs
+//
+// 已安装引擎的版本记录 (本地数据目录下的 engines/installed.json)。
+// 每个版本安装在独立的目录 engines/<引擎 ID>/<版本号> 中，并保留上一个版本，
+// 新版本无法正常运行时可以恢复到上一个版本。
+// 没有记录的引擎 (旧版程序安装的) 仍使用引擎清单中 install_dir 指定的位置。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::AppError;
+use crate::manifest::is_safe_relative_path;
+
+pub const INSTALLS_DIR_NAME: &str = "engines";
+pub const INSTALLS_FILE_NAME: &str = "installed.json";
+
+/// 一个已安装的版本。
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InstalledVersion {
+    // 版本号，为空表示未知 (旧版程序安装的引擎)
+    pub version: String,
+    // 安装目录，相对于本地数据目录
+    pub dir: String,
+    // 入口可执行文件，相对于安装目录
+    pub entry: String,
+    // 安装时间 (Unix 秒)
+    #[serde(default)]
+    pub installed_at: u64,
+}
+
+impl InstalledVersion {
+    pub fn new(version: &str, dir: String, entry: &str) -> Self {
+        let installed_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Self { version: version.to_string(), dir, entry: entry.to_string(), installed_at }
+    }
+
+    pub fn install_path(&self, local_data_dir: &Path) -> PathBuf {
+        local_data_dir.join(&self.dir)
+    }
+
+    pub fn entry_path(&self, local_data_dir: &Path) -> PathBuf {
+        self.install_path(local_data_dir).join(&self.entry)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InstalledEngine {
+    pub current: InstalledVersion,
+    // 上一个版本，新版本未通过自检时恢复到该版本
+    #[serde(default)]
+    pub previous: Option<InstalledVersion>,
+}
+
+#[derive(Default)]
+pub struct InstallRegistry {
+    path: Option<PathBuf>,
+    engines: HashMap<String, InstalledEngine>,
+}
+
+impl InstallRegistry {
+    pub fn load(path: PathBuf) -> Self {
+        let engines = fs::read_to_string(&path).ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { path: Some(path), engines }
+    }
+
+    pub fn get(&self, engine_id: &str) -> Option<&InstalledEngine> {
+        self.engines.get(engine_id)
+    }
+
+    /// 更新 (或在 `installed` 为 None 时删除) 引擎的安装记录并保存。
+    pub fn set(&mut self, engine_id: &str, installed: Option<InstalledEngine>) -> Result<(), AppError> {
+        match installed {
+            Some(installed) => self.engines.insert(engine_id.to_string(), installed),
+            None => self.engines.remove(engine_id),
+        };
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), AppError> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(&self.engines).map_err(std::io::Error::from)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// 指定版本的安装目录 (相对于本地数据目录)。
+fn version_dir(engine_id: &str, version: &str) -> String {
+    format!("{}/{}/{}", INSTALLS_DIR_NAME, engine_id, version)
+}
+
+/// 新版本的安装目录。与当前版本或上一个版本的目录相同时 (重新安装同一版本) 加上时间后缀，
+/// 保证新版本未通过自检时原目录仍然完好。
+pub fn new_version_dir(engine_id: &str, version: &str, installed: Option<&InstalledEngine>) -> String {
+    let dir = version_dir(engine_id, version);
+    let in_use = installed.is_some_and(|r| {
+        std::iter::once(&r.current).chain(r.previous.as_ref()).any(|v| v.dir == dir)
+    });
+    if !in_use {
+        return dir;
+    }
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    format!("{}-{}", dir, secs)
+}
+
+/// 安装记录的当前版本或上一个版本是否使用 `version` 的安装目录与入口。
+pub fn references(installed: &InstalledEngine, version: &InstalledVersion) -> bool {
+    std::iter::once(&installed.current)
+        .chain(installed.previous.as_ref())
+        .any(|v| v.dir == version.dir && v.entry == version.entry)
+}
+
+/// 删除旧版程序安装在清单目录中的引擎。
+///
+/// 安装目录为空 (本地数据目录本身) 时不能删除整个目录：入口位于子目录中时删除该子目录 (如 OCR 引擎)，
+/// 否则删除入口文件及其旁边的语言包目录 (翻译引擎)。
+pub fn remove_legacy_install(local_data_dir: &Path, legacy: &InstalledVersion) {
+    if !legacy.dir.is_empty() {
+        if is_safe_relative_path(&legacy.dir) {
+            println!("[INSTALL] 删除旧版安装目录: {}", legacy.dir);
+            let _ = fs::remove_dir_all(legacy.install_path(local_data_dir));
+        }
+        return;
+    }
+    if !is_safe_relative_path(&legacy.entry) {
+        return;
+    }
+    let entry = Path::new(&legacy.entry);
+    match entry.components().next() {
+        // 入口位于版本目录中的记录不属于旧版安装
+        Some(first) if first.as_os_str() == INSTALLS_DIR_NAME => {}
+        Some(first) if entry.components().count() > 1 => {
+            println!("[INSTALL] 删除旧版安装目录: {:?}", first);
+            let _ = fs::remove_dir_all(local_data_dir.join(first));
+        }
+        _ => {
+            let entry_path = local_data_dir.join(entry);
+            println!("[INSTALL] 删除旧版安装的引擎: {:?}", entry_path);
+            let _ = fs::remove_file(&entry_path);
+            let _ = fs::remove_dir_all(entry_path.with_file_name("packages"));
+        }
+    }
+}
+
+/// 删除引擎目录下除当前版本与上一个版本以外的目录 (失败的安装、更早的版本)。
+pub fn remove_unused_versions(local_data_dir: &Path, engine_id: &str, installed: &InstalledEngine) {
+    let keep: Vec<PathBuf> = std::iter::once(&installed.current)
+        .chain(installed.previous.as_ref())
+        .map(|v| v.install_path(local_data_dir))
+        .collect();
+    let Ok(entries) = fs::read_dir(local_data_dir.join(INSTALLS_DIR_NAME).join(engine_id)) else { return };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() && !keep.contains(&path) {
+            println!("[INSTALL] 删除不再使用的版本: {:?}", path);
+            let _ = fs::remove_dir_all(&path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_data_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("screentranslator-installs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn legacy(dir: &str, entry: &str) -> InstalledVersion {
+        InstalledVersion { version: String::new(), dir: dir.to_string(), entry: entry.to_string(), installed_at: 0 }
+    }
+
+    #[test]
+    fn legacy_install_in_a_subdirectory_is_removed_without_touching_the_data_dir() {
+        let data_dir = temp_data_dir("subdir");
+        fs::create_dir_all(data_dir.join("RapidOCR-json_v0.2.0")).unwrap();
+        fs::write(data_dir.join("RapidOCR-json_v0.2.0/RapidOCR-json.exe"), "").unwrap();
+        fs::write(data_dir.join("translation_cache.json"), "{}").unwrap();
+
+        remove_legacy_install(&data_dir, &legacy("", "RapidOCR-json_v0.2.0/RapidOCR-json.exe"));
+        assert!(!data_dir.join("RapidOCR-json_v0.2.0").exists());
+        assert!(data_dir.join("translation_cache.json").exists());
+        let _ = fs::remove_dir_all(&data_dir);
+    }
+
+    #[test]
+    fn legacy_install_at_the_data_dir_root_removes_only_the_engine_files() {
+        let data_dir = temp_data_dir("root");
+        fs::write(data_dir.join("translate_engine.exe"), "").unwrap();
+        fs::create_dir_all(data_dir.join("packages")).unwrap();
+        fs::create_dir_all(data_dir.join(INSTALLS_DIR_NAME).join("translator/0.2.0")).unwrap();
+
+        remove_legacy_install(&data_dir, &legacy("", "translate_engine.exe"));
+        assert!(!data_dir.join("translate_engine.exe").exists());
+        assert!(!data_dir.join("packages").exists());
+        assert!(data_dir.join(INSTALLS_DIR_NAME).join("translator/0.2.0").exists());
+
+        // 不安全或指向版本目录的记录不做任何删除
+        remove_legacy_install(&data_dir, &legacy("..", "translate_engine.exe"));
+        remove_legacy_install(&data_dir, &legacy("", "engines/translator/0.2.0/translate_engine.exe"));
+        assert!(data_dir.join(INSTALLS_DIR_NAME).join("translator/0.2.0").exists());
+        let _ = fs::remove_dir_all(&data_dir);
+    }
+
+    #[test]
+    fn references_match_current_or_previous_version() {
+        let current = InstalledVersion::new("0.3.0", version_dir("translator", "0.3.0"), "translate_engine.exe");
+        let previous = InstalledVersion::new("0.2.0", version_dir("translator", "0.2.0"), "translate_engine.exe");
+        let installed = InstalledEngine { current, previous: Some(previous) };
+        assert!(references(&installed, &InstalledVersion::new("0.2.0", version_dir("translator", "0.2.0"), "translate_engine.exe")));
+        assert!(!references(&installed, &legacy("", "translate_engine.exe")));
+    }
+}
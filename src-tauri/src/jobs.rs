@@ -0,0 +1,112 @@
+// 文件: src-tauri\src\jobs.rs
+//
+// 可取消的后台任务 (截图识别、翻译等)。
+// 每个任务分配一个 ID 并通过 "job_started" 事件通知前端，前端可调用 `cancel_job` 取消。
+// 取消时中止异步任务，正在等待的引擎调用随之被丢弃，对应的引擎进程也会被终止。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tokio::task::AbortHandle;
+
+use crate::settings::AppState;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct JobInfo {
+    pub job_id: u64,
+    pub kind: String,
+}
+
+struct Job {
+    kind: String,
+    abort: AbortHandle,
+}
+
+#[derive(Default)]
+pub struct JobRegistry {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<u64, Job>>,
+}
+
+impl JobRegistry {
+    /// 取消指定任务。任务不存在（已结束）时返回 false。
+    pub fn cancel(&self, job_id: u64) -> bool {
+        let jobs = self.jobs.lock().unwrap();
+        let Some(job) = jobs.get(&job_id) else { return false };
+        println!("[JOB] 取消任务 #{} ({})", job_id, job.kind);
+        job.abort.abort();
+        true
+    }
+
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.jobs.lock().unwrap().iter()
+            .map(|(id, job)| JobInfo { job_id: *id, kind: job.kind.clone() })
+            .collect()
+    }
+
+    fn remove(&self, job_id: u64) {
+        self.jobs.lock().unwrap().remove(&job_id);
+    }
+}
+
+/// 在后台启动一个可取消的任务，返回任务 ID。
+///
+/// `task` 以任务 ID 为参数创建异步任务，任务推送给前端的事件带上该 ID，前端据此忽略其他任务的事件。
+/// 无论任务正常结束、被取消还是 panic，都会在结束后调用 `on_finish`（参数为任务 ID 以及任务是否正常完成），
+/// 用于释放截图锁、隐藏加载窗口等必须执行的清理工作。
+pub fn spawn_job<T, F, C>(app: &AppHandle, kind: &str, task: T, on_finish: C) -> u64
+where
+    T: FnOnce(u64) -> F,
+    F: Future<Output = ()> + Send + 'static,
+    C: FnOnce(&AppHandle, u64, bool) + Send + 'static,
+{
+    let state: State<AppState> = app.state();
+    let job_id = state.jobs.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+
+    // 先登记再启动监视任务，保证监视任务移除登记时登记已存在
+    let handle = tokio::spawn(task(job_id));
+    state.jobs.jobs.lock().unwrap().insert(job_id, Job {
+        kind: kind.to_string(),
+        abort: handle.abort_handle(),
+    });
+    println!("[JOB] 任务 #{} ({}) 已启动", job_id, kind);
+    let _ = app.emit_all("job_started", JobInfo { job_id, kind: kind.to_string() });
+
+    let app = app.clone();
+    let kind = kind.to_string();
+    tokio::spawn(async move {
+        let completed = match handle.await {
+            Ok(()) => {
+                println!("[JOB] 任务 #{} 已完成", job_id);
+                true
+            }
+            Err(e) if e.is_cancelled() => {
+                println!("[JOB] 任务 #{} 已取消", job_id);
+                false
+            }
+            Err(e) => {
+                eprintln!("[JOB] 任务 #{} 异常终止: {}", job_id, e);
+                false
+            }
+        };
+        app.state::<AppState>().jobs.remove(job_id);
+        on_finish(&app, job_id, completed);
+        let _ = app.emit_all("job_finished", JobInfo { job_id, kind });
+    });
+    job_id
+}
+
+/// 取消正在执行的任务。
+#[tauri::command]
+pub fn cancel_job(job_id: u64, state: State<'_, AppState>) -> bool {
+    state.jobs.cancel(job_id)
+}
+
+/// 列出正在执行的任务。
+#[tauri::command]
+pub fn list_jobs(state: State<'_, AppState>) -> Vec<JobInfo> {
+    state.jobs.list()
+}
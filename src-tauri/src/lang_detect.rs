@@ -0,0 +1,141 @@
+// 文件: src-tauri\src\lang_detect.rs
+//
+// 离线的源语言检测。
+// 先按文字脚本区分中文、日文、韩文、俄文等，拉丁字母文本再使用轻量的三元组 (trigram) 频率模型区分具体语言。
+
+use std::collections::HashMap;
+
+// 各拉丁字母语言最常见的三元组，按频率从高到低排列，'_' 表示词边界
+const TRIGRAM_PROFILES: &[(&str, &[&str])] = &[
+    ("en", &["_th", "the", "he_", "_an", "nd_", "and", "ing", "ng_", "_of", "of_", "_to", "to_", "ed_", "_in", "ion",
+             "is_", "tio", "er_", "_is", "ent", "re_", "hat", "tha", "at_", "es_", "_it", "for", "_fo", "on_", "_wh"]),
+    ("de", &["en_", "er_", "_de", "der", "ie_", "ich", "ein", "sch", "die", "_di", "che", "_ei", "und", "_un", "nd_",
+             "den", "cht", "ch_", "_da", "ten", "gen", "ung", "_ge", "in_", "te_", "ine", "es_", "ber", "_zu", "ist"]),
+    ("fr", &["es_", "_de", "de_", "le_", "_le", "ent", "_la", "la_", "re_", "on_", "nt_", "_pa", "ion", "les", "_et",
+             "et_", "tio", "_co", "que", "ue_", "_qu", "des", "_un", "ne_", "men", "ait", "our", "ous", "_po", "eur"]),
+    ("es", &["de_", "_de", "os_", "_la", "la_", "el_", "es_", "_el", "_qu", "que", "ue_", "en_", "ion", "_co", "as_",
+             "ent", "_en", "_lo", "los", "ado", "aci", "cio", "_se", "_pa", "con", "ara", "par", "_es", "del", "_y_"]),
+    ("it", &["_di", "di_", "to_", "la_", "_la", "re_", "del", "_de", "che", "_ch", "he_", "ell", "one", "lla", "_il",
+             "il_", "ion", "ent", "_in", "_co", "zio", "le_", "_pe", "per", "_no", "ato", "ta_", "no_", "are", "gli"]),
+    ("pt", &["de_", "_de", "os_", "_qu", "que", "ue_", "do_", "_do", "da_", "_da", "ão_", "ção", "_co", "ent", "_em",
+             "em_", "es_", "as_", "_e_", "_pa", "men", "com", "nte", "ra_", "_se", "_pr", "par", "_na", "uma", "_um"]),
+    ("nl", &["en_", "_de", "de_", "an_", "et_", "_he", "het", "_va", "van", "er_", "_en", "ij_", "_ee", "een", "_in",
+             "den", "ver", "_ge", "aar", "oor", "_ov", "_da", "dat", "ing", "_te", "_zi", "sch", "_ij", "_ni", "nie"]),
+];
+
+// 只在个别语言中出现的字母，出现时为对应语言加分
+const DISTINCTIVE_LETTERS: &[(&str, &str)] = &[
+    ("de", "äöüß"),
+    ("fr", "éèêëçàùûœ"),
+    ("es", "ñ¿¡áíóú"),
+    ("pt", "ãõâêç"),
+    ("it", "àèìòù"),
+];
+
+/// 检测文本的语言，返回语言代码 (如 "zh"、"ja"、"en")；文本过短或无法判断时返回 `None`。
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let mut han = 0usize;
+    let mut kana = 0usize;
+    let mut hangul = 0usize;
+    let mut cyrillic = 0usize;
+    let mut latin = 0usize;
+    for c in text.chars() {
+        match c as u32 {
+            0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => kana += 1,
+            0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF => han += 1,
+            0xAC00..=0xD7AF | 0x1100..=0x11FF | 0x3130..=0x318F => hangul += 1,
+            0x0400..=0x04FF => cyrillic += 1,
+            _ if (c.is_ascii() && c.is_alphabetic()) || ('\u{00C0}'..='\u{024F}').contains(&c) => latin += 1,
+            _ => {}
+        }
+    }
+
+    let total = han + kana + hangul + cyrillic + latin;
+    if total < 2 {
+        return None;
+    }
+
+    // 日文通常混用汉字与假名，只要假名占有一定比例即可判定为日文
+    if kana > 0 && kana * 10 >= (han + kana) {
+        return Some("ja");
+    }
+    if hangul * 2 >= total {
+        return Some("ko");
+    }
+    if han * 2 >= total {
+        return Some("zh");
+    }
+    if cyrillic * 2 >= total {
+        return Some("ru");
+    }
+    if latin * 2 >= total {
+        return Some(detect_latin_language(text));
+    }
+    None
+}
+
+/// 使用三元组频率模型在拉丁字母语言之间进行区分，无法区分时默认为英文。
+fn detect_latin_language(text: &str) -> &'static str {
+    let normalized: String = text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphabetic() { c } else { '_' })
+        .collect();
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for word in normalized.split('_').filter(|w| !w.is_empty()) {
+        let padded: Vec<char> = format!("_{}_", word).chars().collect();
+        for window in padded.windows(3) {
+            *counts.entry(window.iter().collect()).or_default() += 1;
+        }
+    }
+
+    let mut best = ("en", 0usize);
+    for (lang, profile) in TRIGRAM_PROFILES {
+        // 排名越靠前的三元组权重越高
+        let mut score: usize = profile.iter().enumerate()
+            .map(|(rank, trigram)| counts.get(*trigram).copied().unwrap_or(0) * (profile.len() - rank))
+            .sum();
+        if let Some((_, letters)) = DISTINCTIVE_LETTERS.iter().find(|(l, _)| l == lang) {
+            score += normalized.chars().filter(|c| letters.contains(*c)).count() * profile.len();
+        }
+        if score > best.1 {
+            best = (lang, score);
+        }
+    }
+    best.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_scripts() {
+        assert_eq!(detect_language("今天天气很好，我们去公园吧。"), Some("zh"));
+        assert_eq!(detect_language("今日はいい天気ですね。"), Some("ja"));
+        assert_eq!(detect_language("カタカナだけ"), Some("ja"));
+        assert_eq!(detect_language("오늘 날씨가 좋네요"), Some("ko"));
+        assert_eq!(detect_language("Сегодня хорошая погода"), Some("ru"));
+    }
+
+    #[test]
+    fn detects_latin_languages() {
+        assert_eq!(detect_language("The quick brown fox jumps over the lazy dog and the cat."), Some("en"));
+        assert_eq!(detect_language("Der schnelle braune Fuchs springt über den faulen Hund und die Katze."), Some("de"));
+        assert_eq!(detect_language("Le renard brun rapide saute par-dessus le chien paresseux et les chats."), Some("fr"));
+        assert_eq!(detect_language("El rápido zorro marrón salta sobre el perro perezoso y los gatos."), Some("es"));
+    }
+
+    #[test]
+    fn short_or_symbol_only_text_is_undetermined() {
+        assert_eq!(detect_language(""), None);
+        assert_eq!(detect_language("a"), None);
+        assert_eq!(detect_language("123 + 456 = 579 !!"), None);
+    }
+
+    #[test]
+    fn mixed_text_follows_the_dominant_script() {
+        assert_eq!(detect_language("点击 OK 按钮继续安装程序"), Some("zh"));
+        assert_eq!(detect_language("Press 確定 to continue the installation"), Some("en"));
+    }
+}
@@ -0,0 +1,326 @@
+// 文件: src-tauri\src\layout.rs
+//
+// 基于文本框几何信息的段落重建。
+// 识别引擎按行输出文本，这里根据行的位置将其还原为分栏、段落，
+// 合并被连字符断开的单词，并避免在中日文字符之间插入多余的空格。
+
+use crate::ocr::{OcrLine, OcrResult};
+
+/// 将识别结果重排为便于翻译和粘贴的文本。
+///
+/// - `preserve_line_breaks` 为 `true` 时段落内保留原始换行，段落之间以空行分隔；
+/// - 为 `false` 时段落内的行合并为一行，段落之间以换行分隔。
+pub fn reflow(result: &OcrResult, preserve_line_breaks: bool) -> String {
+    let lines: Vec<&OcrLine> = result.blocks.iter()
+        .filter(|line| !line.text.trim().is_empty())
+        .collect();
+    if lines.is_empty() {
+        return String::new();
+    }
+
+    // 引擎没有返回文本框时无法分析版面，按原顺序作为一个段落处理
+    if lines.iter().all(|line| line.bbox == [[0; 2]; 4]) {
+        let texts: Vec<&str> = lines.iter().map(|line| line.text.trim()).collect();
+        return join_paragraph(&texts, preserve_line_breaks);
+    }
+
+    let line_height = median_line_height(&lines);
+    let paragraph_separator = if preserve_line_breaks { "\n\n" } else { "\n" };
+
+    let mut paragraphs = Vec::new();
+    for column in split_columns(lines, line_height) {
+        let rows = group_rows(column, line_height);
+        for paragraph in split_paragraphs(&rows, line_height) {
+            let texts: Vec<&str> = paragraph.iter().map(|row| row.text.as_str()).collect();
+            paragraphs.push(join_paragraph(&texts, preserve_line_breaks));
+        }
+    }
+    paragraphs.join(paragraph_separator)
+}
+
+/// 同一视觉行上的文本（可能由多个文本框组成）。
+struct Row {
+    text: String,
+    left: i32,
+    right: i32,
+    top: i32,
+    bottom: i32,
+}
+
+fn median_line_height(lines: &[&OcrLine]) -> i32 {
+    let mut heights: Vec<i32> = lines.iter().map(|line| line.height()).filter(|h| *h > 0).collect();
+    if heights.is_empty() {
+        return 1;
+    }
+    heights.sort_unstable();
+    heights[heights.len() / 2].max(1)
+}
+
+// 间隔两侧在同一垂直范围内都至少有这么多行时，才视为分栏
+const MIN_COLUMN_LINES: usize = 2;
+
+/// 将文本行在水平方向上的投影进行切分，空白间隔超过两倍行高、
+/// 且在垂直方向上连续多行都存在时视为分栏。只出现在单独一行上的宽间隔 (表格单元格、分散排列的标题) 不分栏。
+fn split_columns(mut lines: Vec<&OcrLine>, line_height: i32) -> Vec<Vec<&OcrLine>> {
+    lines.sort_by_key(|line| line.left());
+
+    let min_gap = line_height * 2;
+    let mut candidates: Vec<Vec<&OcrLine>> = Vec::new();
+    let mut column_right = i32::MIN;
+    for line in lines {
+        match candidates.last_mut() {
+            Some(column) if line.left() - column_right < min_gap => {
+                column_right = column_right.max(line.right());
+                column.push(line);
+            }
+            _ => {
+                column_right = line.right();
+                candidates.push(vec![line]);
+            }
+        }
+    }
+
+    let mut columns: Vec<Vec<&OcrLine>> = Vec::new();
+    for candidate in candidates {
+        match columns.last_mut() {
+            Some(column) if !is_column_break(column, &candidate) => column.extend(candidate),
+            _ => columns.push(candidate),
+        }
+    }
+    columns
+}
+
+/// 左右两组文本行之间的间隔是否为分栏：两组在共同的垂直范围内都至少有 `MIN_COLUMN_LINES` 行。
+fn is_column_break(left: &[&OcrLine], right: &[&OcrLine]) -> bool {
+    let span = |lines: &[&OcrLine]| {
+        (lines.iter().map(|line| line.top()).min().unwrap_or(0), lines.iter().map(|line| line.bottom()).max().unwrap_or(0))
+    };
+    let (left_top, left_bottom) = span(left);
+    let (right_top, right_bottom) = span(right);
+    let (top, bottom) = (left_top.max(right_top), left_bottom.min(right_bottom));
+    let lines_within = |lines: &[&OcrLine]| {
+        lines.iter().filter(|line| (top..=bottom).contains(&((line.top() + line.bottom()) / 2))).count()
+    };
+    lines_within(left) >= MIN_COLUMN_LINES && lines_within(right) >= MIN_COLUMN_LINES
+}
+
+/// 将同一栏内垂直中心接近的文本框合并为行，并按从上到下的顺序排列。
+fn group_rows(mut column: Vec<&OcrLine>, line_height: i32) -> Vec<Row> {
+    column.sort_by_key(|line| (line.top() + line.bottom(), line.left()));
+
+    let mut rows: Vec<Vec<&OcrLine>> = Vec::new();
+    for line in column {
+        let center = (line.top() + line.bottom()) / 2;
+        match rows.last_mut() {
+            Some(row) if (center - row_center(row)).abs() * 2 < line_height => row.push(line),
+            _ => rows.push(vec![line]),
+        }
+    }
+
+    rows.into_iter()
+        .map(|mut row| {
+            row.sort_by_key(|line| line.left());
+            let texts: Vec<&str> = row.iter().map(|line| line.text.trim()).collect();
+            Row {
+                text: join_paragraph(&texts, false),
+                left: row.iter().map(|line| line.left()).min().unwrap_or(0),
+                right: row.iter().map(|line| line.right()).max().unwrap_or(0),
+                top: row.iter().map(|line| line.top()).min().unwrap_or(0),
+                bottom: row.iter().map(|line| line.bottom()).max().unwrap_or(0),
+            }
+        })
+        .collect()
+}
+
+fn row_center(row: &[&OcrLine]) -> i32 {
+    let sum: i32 = row.iter().map(|line| (line.top() + line.bottom()) / 2).sum();
+    sum / row.len() as i32
+}
+
+/// 根据行距、缩进以及上一行是否提前结束，将行划分为段落。
+fn split_paragraphs(rows: &[Row], line_height: i32) -> Vec<Vec<&Row>> {
+    let column_left = rows.iter().map(|row| row.left).min().unwrap_or(0);
+    let column_right = rows.iter().map(|row| row.right).max().unwrap_or(0);
+    let column_width = (column_right - column_left).max(1);
+
+    let mut paragraphs: Vec<Vec<&Row>> = Vec::new();
+    for row in rows {
+        let starts_new = match paragraphs.last().and_then(|p| p.last()) {
+            None => true,
+            Some(prev) => {
+                let gap = row.top - prev.bottom;
+                let large_gap = gap * 4 > line_height * 3;
+                // 首行缩进：本行明显比栏左边界更靠右，而上一行不是
+                let indented = row.left - column_left > line_height
+                    && prev.left - column_left <= line_height / 2;
+                // 上一行以句末标点结尾且未写满整栏
+                let prev_short = (prev.right - column_left) * 5 < column_width * 4
+                    && prev.text.chars().last().is_some_and(is_sentence_end);
+                large_gap || indented || prev_short
+            }
+        };
+        if starts_new {
+            paragraphs.push(vec![row]);
+        } else if let Some(paragraph) = paragraphs.last_mut() {
+            paragraph.push(row);
+        }
+    }
+    paragraphs
+}
+
+/// 拼接段落内的各行。
+///
+/// 保留换行时直接以换行连接；否则合并连字符断词，并仅在非中日文字符之间插入空格。
+fn join_paragraph(texts: &[&str], preserve_line_breaks: bool) -> String {
+    if preserve_line_breaks {
+        return texts.join("\n");
+    }
+
+    let mut out = String::new();
+    for text in texts {
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let next_first = text.chars().next();
+        match (out.chars().last(), next_first) {
+            (None, _) => {}
+            // 英文单词被行尾连字符拆开: "transla-" + "tion" -> "translation"
+            (Some('-'), Some(next)) if next.is_lowercase() && ends_with_hyphenated_word(&out) => {
+                out.pop();
+            }
+            (Some(prev), Some(next)) if is_cjk(prev) || is_cjk(next) => {}
+            _ => out.push(' '),
+        }
+        out.push_str(text);
+    }
+    out
+}
+
+fn ends_with_hyphenated_word(text: &str) -> bool {
+    let mut chars = text.chars().rev();
+    chars.next() == Some('-') && chars.next().is_some_and(|c| c.is_alphabetic() && !is_cjk(c))
+}
+
+fn is_sentence_end(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | ':' | '。' | '！' | '？' | '：' | '…')
+}
+
+/// 判断字符是否属于书写时词与词之间不使用空格的文字（汉字、假名及全角标点）。
+///
+/// 韩文使用空格分词，因此不包含在内。
+pub fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3000..=0x303F     // CJK 符号和标点
+        | 0x3040..=0x30FF   // 平假名、片假名
+        | 0x3400..=0x4DBF   // CJK 扩展 A
+        | 0x4E00..=0x9FFF   // CJK 统一汉字
+        | 0xF900..=0xFAFF   // CJK 兼容汉字
+        | 0xFF00..=0xFFEF   // 全角字符
+        | 0x20000..=0x2FA1F // CJK 扩展 B 及以后
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str, left: i32, top: i32, right: i32, bottom: i32) -> OcrLine {
+        OcrLine {
+            text: text.to_string(),
+            bbox: [[left, top], [right, top], [right, bottom], [left, bottom]],
+            score: 1.0,
+        }
+    }
+
+    fn result(blocks: Vec<OcrLine>) -> OcrResult {
+        OcrResult { blocks }
+    }
+
+    #[test]
+    fn hyphenated_words_are_joined() {
+        let ocr = result(vec![
+            line("This is a transla-", 0, 0, 400, 20),
+            line("tion test.", 0, 25, 150, 45),
+        ]);
+        assert_eq!(reflow(&ocr, false), "This is a translation test.");
+        assert_eq!(reflow(&ocr, true), "This is a transla-\ntion test.");
+    }
+
+    #[test]
+    fn large_gaps_and_indents_start_new_paragraphs() {
+        let ocr = result(vec![
+            line("First paragraph line", 0, 0, 400, 20),
+            line("Second paragraph", 0, 60, 400, 80),
+            line("Indented third", 40, 85, 400, 105),
+        ]);
+        assert_eq!(reflow(&ocr, false), "First paragraph line\nSecond paragraph\nIndented third");
+        assert_eq!(reflow(&ocr, true), "First paragraph line\n\nSecond paragraph\n\nIndented third");
+    }
+
+    #[test]
+    fn short_line_ending_a_sentence_ends_the_paragraph() {
+        let ocr = result(vec![
+            line("A full width line of text", 0, 0, 400, 20),
+            line("ends here.", 0, 25, 120, 45),
+            line("Next one starts", 0, 50, 400, 70),
+        ]);
+        assert_eq!(reflow(&ocr, false), "A full width line of text ends here.\nNext one starts");
+    }
+
+    #[test]
+    fn columns_are_read_one_after_another() {
+        let ocr = result(vec![
+            line("Right one", 300, 0, 500, 20),
+            line("Left one", 0, 0, 200, 20),
+            line("Right two", 300, 25, 500, 45),
+            line("Left two", 0, 25, 200, 45),
+        ]);
+        assert_eq!(reflow(&ocr, false), "Left one Left two\nRight one Right two");
+    }
+
+    #[test]
+    fn a_wide_gap_on_a_single_row_is_not_a_column_break() {
+        let ocr = result(vec![
+            line("Name", 0, 0, 80, 20),
+            line("Value", 400, 0, 480, 20),
+            line("A paragraph below the table row", 0, 30, 480, 50),
+        ]);
+        assert_eq!(reflow(&ocr, false), "Name Value A paragraph below the table row");
+
+        let heading = result(vec![
+            line("Chapter", 0, 0, 100, 20),
+            line("One", 300, 0, 360, 20),
+        ]);
+        assert_eq!(reflow(&heading, false), "Chapter One");
+    }
+
+    #[test]
+    fn boxes_on_the_same_row_are_merged_left_to_right() {
+        let ocr = result(vec![
+            line("world", 120, 2, 220, 22),
+            line("Hello", 0, 0, 100, 20),
+        ]);
+        assert_eq!(reflow(&ocr, true), "Hello world");
+    }
+
+    #[test]
+    fn cjk_lines_are_joined_without_spaces() {
+        let ocr = result(vec![
+            line("今天天气", 0, 0, 400, 20),
+            line("很好", 0, 25, 100, 45),
+        ]);
+        assert_eq!(reflow(&ocr, false), "今天天气很好");
+    }
+
+    #[test]
+    fn results_without_boxes_keep_engine_order() {
+        let ocr = result(vec![
+            line("second", 0, 0, 0, 0),
+            line("first", 0, 0, 0, 0),
+            line("  ", 0, 0, 0, 0),
+        ]);
+        assert_eq!(reflow(&ocr, true), "second\nfirst");
+        assert_eq!(reflow(&ocr, false), "second first");
+    }
+}
@@ -2,19 +2,40 @@
 
 mod capture;
 mod commands;
+mod error;
+mod glossary;
+mod i18n;
+mod installs;
+mod jobs;
+mod lang_detect;
+mod layout;
+mod manifest;
+mod ocr;
+mod segmenter;
 mod settings;
+mod translation_cache;
 mod translator;
+mod worker;
 
 use tauri::{
     AppHandle, GlobalShortcutManager, Manager, State,
     PhysicalSize, PhysicalPosition, Size, Position
 };
 use tauri_plugin_autostart::MacosLauncher;
+use error::AppError;
 use settings::{AppState, AppSettings};
+use translation_cache::TranslationCache;
+use glossary::Glossary;
+use installs::InstallRegistry;
 use std::sync::atomic::{Ordering};
 use base64::{Engine as _, engine::general_purpose};
 use std::fs;
 use std::path::PathBuf; // 引入 PathBuf 用于处理文件路径
+use std::time::Duration;
+
+// 常驻引擎进程的空闲超时时间及检查间隔
+const WORKER_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+const WORKER_IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
 
 // --- 事件 Payload 定义 ---
 #[derive(Clone, serde::Serialize)]
@@ -22,9 +43,9 @@ struct ImageViewerPayload { image_data_url: String, image_path: String }
 #[derive(Clone, serde::Serialize)]
 struct ScreenshotPayload { image_data_url: String }
 #[derive(Clone, serde::Serialize)]
-struct OcrPayload { original_text: Option<String>, error_message: Option<String>, image_path: String }
+struct OcrPayload { job_id: u64, original_text: Option<String>, error: Option<AppError>, ocr_result: Option<ocr::OcrResult>, image_path: String }
 #[derive(Clone, serde::Serialize)]
-struct TranslationUpdatePayload { translated_text: Option<String>, error_message: Option<String> }
+struct TranslationUpdatePayload { job_id: u64, translated_text: Option<String>, error: Option<AppError>, translator_backend: Option<String> }
 
 /// [新增] 辅助函数，用于处理命令行参数
 ///
@@ -86,14 +107,25 @@ fn main() {
             commands::process_screenshot_area,
             commands::process_image_from_path,
             commands::cancel_screenshot,
+            jobs::cancel_job,
+            jobs::list_jobs,
             settings::get_settings,
             settings::set_settings,
+            settings::get_glossary,
+            settings::set_glossary,
+            settings::import_glossary,
             settings::copy_image_to_clipboard,
             settings::save_image_to_desktop,
             commands::check_ocr_status,
+            commands::list_ocr_engines,
             commands::download_ocr,
             commands::check_translator_status,
+            commands::get_supported_language_pairs,
             commands::download_translator,
+            commands::install_engine_from_path,
+            commands::check_engine_updates,
+            commands::get_translation_cache_stats,
+            commands::purge_translation_cache,
             commands::get_last_ocr_result // --- 新增注册命令 ---
         ])
         // 应用程序初始化设置
@@ -109,10 +141,31 @@ fn main() {
             let settings = AppSettings::load(&app.path_resolver()).unwrap_or_default();
 
             *state.settings.lock().unwrap() = settings.clone();
+            i18n::set_language(&settings.ui_language);
+
+            if let Some(data_dir) = app.path_resolver().app_local_data_dir() {
+                *state.translation_cache.lock().unwrap() = TranslationCache::load(data_dir.join(translation_cache::CACHE_FILE_NAME));
+                *state.engine_installs.lock().unwrap() = InstallRegistry::load(data_dir.join(installs::INSTALLS_DIR_NAME).join(installs::INSTALLS_FILE_NAME));
+            }
+            if let Some(config_dir) = app.path_resolver().app_config_dir() {
+                *state.glossary.lock().unwrap() = Glossary::load(config_dir.join(glossary::GLOSSARY_FILE_NAME));
+                *state.engine_manifest.lock().unwrap() = manifest::load_active(&config_dir, &settings.engine_manifest_url);
+            }
 
             register_global_shortcut(app.handle(), &settings.shortcut).unwrap_or_else(|e| eprintln!("主快捷键注册失败: {}", e));
             register_view_image_shortcut(app.handle(), &settings.view_image_shortcut).unwrap_or_else(|e| eprintln!("查看快捷键注册失败: {}", e));
 
+            // 后台定期清理已崩溃的常驻引擎进程、关闭空闲的进程，并保存翻译缓存
+            let idle_handle = app.handle();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(WORKER_IDLE_CHECK_INTERVAL);
+                let state: State<AppState> = idle_handle.state();
+                state.engine_workers.check_health();
+                state.engine_workers.shutdown_idle(WORKER_IDLE_TIMEOUT);
+                state.translation_cache.lock().unwrap().flush();
+            });
+
+            update_window_titles(&app.handle());
             if let Some(main_window) = app.get_window("main") {
                 main_window.show()?;
             }
@@ -120,7 +173,24 @@ fn main() {
         })
         .build(tauri::generate_context!())
         .expect("Tauri 构建失败")
-        .run(|_app_handle, _event| {});
+        .run(|app_handle, event| {
+            // 程序退出时保存翻译缓存并关闭常驻引擎进程，避免残留
+            if let tauri::RunEvent::Exit = event {
+                let state: State<AppState> = app_handle.state();
+                state.translation_cache.lock().unwrap().flush();
+                // 不等待正在处理的请求 (最长可达引擎超时时间)，直接终止进程
+                state.engine_workers.kill_all();
+            }
+        });
+}
+
+/// 按当前界面语言设置窗口标题 (配置文件中的标题与语言无关，启动与切换界面语言时更新)。
+pub fn update_window_titles(app: &AppHandle) {
+    for (label, key) in [("main", "window.settings"), ("image_viewer", "window.image_viewer")] {
+        if let Some(window) = app.get_window(label) {
+            let _ = window.set_title(&i18n::t(key));
+        }
+    }
 }
 
 /// 显示结果窗口并填充缓存的数据
@@ -145,15 +215,19 @@ pub fn show_results_window_with_cache(app: &AppHandle) {
         window.set_focus().unwrap();
 
         window.emit("ocr_result", OcrPayload {
+            job_id: data.job_id,
             original_text: data.original_text,
-            error_message: None,
+            error: None,
+            ocr_result: data.ocr_result,
             image_path: data.image_path,
         }).unwrap();
 
         if let Some(trans) = data.translated_text {
             window.emit("translation_update", TranslationUpdatePayload {
+                job_id: data.job_id,
                 translated_text: Some(trans),
-                error_message: None,
+                error: None,
+                translator_backend: data.translator_backend,
             }).unwrap();
         }
     }
@@ -177,17 +251,28 @@ pub fn register_global_shortcut(app_handle: AppHandle, shortcut: &str) -> Result
 
         app_handle.run_on_main_thread(move || {
             let inner_state: State<AppState> = handle.state();
-            match crate::capture::capture_fullscreen() {
-                Ok(image) => {
+            let capture_scope = inner_state.settings.lock().unwrap().capture_scope.clone();
+            match crate::capture::capture_fullscreen(&capture_scope) {
+                Ok(capture) => {
+                    let image = capture.image;
                     let img_width = image.width();
                     let img_height = image.height();
+                    // 截图窗口需要覆盖被截取的显示器（或整个虚拟桌面），原点可能为负数
+                    let position = Position::Physical(PhysicalPosition { x: capture.x, y: capture.y });
 
+                    let data_url = match crate::capture::encode_image_to_data_url(&image) {
+                        Ok(data_url) => data_url,
+                        Err(e) => {
+                            eprintln!("全屏截图失败: {}", e);
+                            inner_state.is_capturing.store(false, Ordering::SeqCst);
+                            return;
+                        }
+                    };
                     *inner_state.fullscreen_capture.lock().unwrap() = Some(image.clone());
-                    let data_url = crate::capture::encode_image_to_data_url(&image).unwrap();
 
                     if let Some(w) = handle.get_window("screenshot") {
                         w.set_size(Size::Physical(PhysicalSize { width: img_width, height: img_height })).unwrap();
-                        w.set_position(Position::Physical(PhysicalPosition { x: 0, y: 0 })).unwrap();
+                        w.set_position(position).unwrap();
                         w.emit("initialize-screenshot", ScreenshotPayload{image_data_url: data_url}).unwrap();
                         w.show().unwrap();
                         w.set_focus().unwrap();
@@ -196,7 +281,7 @@ pub fn register_global_shortcut(app_handle: AppHandle, shortcut: &str) -> Result
                             .title("").decorations(false).transparent(true).visible(false).skip_taskbar(true)
                             .always_on_top(true).resizable(false).build().unwrap();
                         w.set_size(Size::Physical(PhysicalSize { width: img_width, height: img_height })).unwrap();
-                        w.set_position(Position::Physical(PhysicalPosition { x: 0, y: 0 })).unwrap();
+                        w.set_position(position).unwrap();
                         w.emit("initialize-screenshot", ScreenshotPayload{image_data_url: data_url}).unwrap();
                         w.show().unwrap();
                         w.set_focus().unwrap();
@@ -250,7 +335,7 @@ pub fn register_view_image_shortcut(app_handle: AppHandle, shortcut: &str) -> Re
                             window.show().unwrap(); window.set_focus().unwrap();
                         } else {
                             let _ = tauri::WindowBuilder::new(&handle_main, "image_viewer", tauri::WindowUrl::App("image_viewer.html".into()))
-                                .title("截图预览").decorations(false).transparent(true).resizable(true).skip_taskbar(true).visible(false)
+                                .title(i18n::t("window.image_viewer")).decorations(false).transparent(true).resizable(true).skip_taskbar(true).visible(false)
                                 .build().unwrap().emit("display-image", payload);
                         }
                     }).unwrap();
@@ -0,0 +1,423 @@
+// 文件: src-tauri\src\manifest.rs
+//
+// 引擎清单：描述每个本地引擎的版本、各平台的安装包、安装目录与入口可执行文件。
+// 程序内置一份清单 (src-tauri/engines.json)，配置目录下存在同名文件时优先使用，
+// 设置中配置了清单镜像地址时，检查更新会从该地址获取最新的清单比较版本，下载或更新引擎时启用该清单，
+// 因此发布新版本引擎时只需更新清单，无需重新编译程序。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::i18n::tf;
+use crate::installs::{InstalledEngine, InstalledVersion};
+use crate::settings::AppState;
+
+pub const MANIFEST_FILE_NAME: &str = "engines.json";
+// 从镜像获取并启用的清单，与用户自行放置的 engines.json 分开保存，清空镜像地址时删除
+pub const MIRROR_MANIFEST_FILE_NAME: &str = "engines.mirror.json";
+const BUILTIN_MANIFEST: &str = include_str!("../engines.json");
+
+/// 安装包的格式。`File` 表示直接使用下载的文件 (如单个可执行文件)，不需要解压。
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveType {
+    #[serde(rename = "7z")]
+    SevenZ,
+    Zip,
+    File,
+}
+
+/// 一个安装包。压缩包解压到引擎的安装目录，`File` 类型的文件以 `file_name` 保存到安装目录。
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Artifact {
+    // 安装包 ID，同时作为下载进度事件的 target
+    pub id: String,
+    pub url: String,
+    pub archive: ArchiveType,
+    #[serde(default)]
+    pub file_name: Option<String>,
+    // SHA-256 (十六进制) 与字节数，下载完成后据此校验；未登记 SHA-256 的文件不予安装，字节数为空时不校验大小
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+}
+
+impl Artifact {
+    /// `File` 类型安装包在安装目录中的文件名，未指定时取 URL 的最后一段。
+    pub fn target_file_name(&self) -> &str {
+        self.file_name.as_deref().unwrap_or_else(|| self.url_file_name())
+    }
+
+    /// 是否登记了 SHA-256。未登记的安装包无法确认来源，不予安装。
+    pub fn has_checksum(&self) -> bool {
+        self.sha256.as_deref().is_some_and(|s| !s.trim().is_empty())
+    }
+
+    /// URL 的最后一段，用于提示中指代该安装包。
+    pub fn url_file_name(&self) -> &str {
+        self.url.rsplit('/').next().unwrap_or(&self.id)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EngineSpec {
+    // 引擎 ID，与常驻进程池中的名称相同 ("rapidocr"、"translator")
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    // 未记录安装版本时 (旧版程序安装的引擎) 使用的安装目录，相对于本地数据目录，
+    // 为空表示本地数据目录本身。新安装的版本位于 engines/<引擎 ID>/<版本号>
+    #[serde(default)]
+    pub install_dir: String,
+    // 入口可执行文件，相对于安装目录
+    pub entry: String,
+    // 以常驻模式启动引擎的参数
+    #[serde(default)]
+    pub args: Vec<String>,
+    // 常驻模式下引擎初始化完成时输出的标志，为空表示启动后即可接收请求
+    #[serde(default)]
+    pub ready_marker: Option<String>,
+    // 平台 ("windows-x86_64" 等) -> 需要依次下载安装的文件
+    #[serde(default)]
+    pub artifacts: HashMap<String, Vec<Artifact>>,
+}
+
+impl EngineSpec {
+    pub fn install_path(&self, local_data_dir: &Path) -> PathBuf {
+        local_data_dir.join(&self.install_dir)
+    }
+
+    pub fn entry_path(&self, local_data_dir: &Path) -> PathBuf {
+        self.install_path(local_data_dir).join(&self.entry)
+    }
+
+    /// 入口可执行文件的文件名，用于在用户提供的安装包中定位引擎。
+    pub fn entry_file_name(&self) -> &str {
+        Path::new(&self.entry).file_name().and_then(|n| n.to_str()).unwrap_or(&self.entry)
+    }
+
+    /// 检查清单中会被拼接为本地路径的字段，避免被篡改的清单将文件写到或执行本地数据目录以外的位置：
+    /// 引擎 ID、版本号、安装包 ID 与安装包的文件名只能是单个路径组成部分，
+    /// 入口与安装目录只能是由安全的组成部分构成的相对路径 (安装目录可以为空)。
+    /// 每个安装包都必须登记 SHA-256。
+    fn validate(&self) -> Result<(), AppError> {
+        if let Some(artifact) = self.artifacts.values().flatten().find(|a| !a.has_checksum()) {
+            return Err(AppError::ManifestInvalid {
+                detail: tf("detail.manifest_missing_checksum", &[("engine", &self.id), ("artifact", &artifact.id)]),
+            });
+        }
+        let mut checks: Vec<(&str, &str, bool)> = vec![
+            ("id", &self.id, is_safe_component(&self.id)),
+            ("version", &self.version, is_safe_component(&self.version)),
+            ("entry", &self.entry, is_safe_relative_path(&self.entry)),
+            ("install_dir", &self.install_dir, self.install_dir.is_empty() || is_safe_relative_path(&self.install_dir)),
+        ];
+        for artifact in self.artifacts.values().flatten() {
+            checks.push(("artifacts.id", &artifact.id, is_safe_component(&artifact.id)));
+            if artifact.archive == ArchiveType::File {
+                let file_name = artifact.target_file_name();
+                checks.push(("artifacts.file_name", file_name, is_safe_component(file_name)));
+            }
+        }
+        match checks.into_iter().find(|(_, _, ok)| !ok) {
+            Some((field, value, _)) => Err(AppError::ManifestInvalid {
+                detail: tf("detail.manifest_unsafe_path", &[("engine", &self.id), ("field", &field), ("value", &value)]),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// 当前平台需要下载的安装包，清单中没有当前平台时返回 None。
+    pub fn platform_artifacts(&self) -> Option<&[Artifact]> {
+        self.artifacts.get(&current_platform()).map(Vec::as_slice)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EngineManifest {
+    pub engines: Vec<EngineSpec>,
+}
+
+impl Default for EngineManifest {
+    fn default() -> Self {
+        serde_json::from_str(BUILTIN_MANIFEST).expect("内置的引擎清单格式错误")
+    }
+}
+
+impl EngineManifest {
+    /// 从镜像地址获取清单。不符合要求 (见 `EngineSpec::validate`) 的清单不予采用。
+    pub async fn fetch(url: &str) -> Result<Self, AppError> {
+        println!("[MANIFEST] 从镜像获取引擎清单: {}", url);
+        let manifest = reqwest::get(url).await
+            .and_then(|res| res.error_for_status())
+            .map_err(|e| AppError::Network { service: "manifest".to_string(), detail: e.to_string() })?
+            .json::<Self>().await
+            .map_err(|e| AppError::ManifestInvalid { detail: e.to_string() })?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// 读取配置目录下的清单，文件不存在、格式错误、包含不安全的路径或缺少校验值时使用内置清单。
+    pub fn load(path: PathBuf) -> Self {
+        let Ok(content) = fs::read_to_string(&path) else { return Self::default() };
+        let parsed = serde_json::from_str::<Self>(&content).map_err(|e| e.to_string())
+            .and_then(|manifest| manifest.validate().map(|_| manifest).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(manifest) => {
+                println!("[MANIFEST] 使用自定义引擎清单: {:?}", path);
+                manifest
+            }
+            Err(e) => {
+                eprintln!("[MANIFEST] 引擎清单 {:?} 格式错误，使用内置清单: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// 保存到配置目录，此后启动时使用该清单。
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(std::io::Error::from)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn engine(&self, id: &str) -> Option<&EngineSpec> {
+        self.engines.iter().find(|e| e.id == id)
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        self.engines.iter().try_for_each(EngineSpec::validate)
+    }
+}
+
+/// 是否为可以安全拼接到本地路径中的单个组成部分：非空，不是 "." 或 ".."，
+/// 不含路径分隔符与盘符、备用数据流使用的冒号。
+fn is_safe_component(value: &str) -> bool {
+    !value.trim().is_empty()
+        && value != "."
+        && value != ".."
+        && !value.contains(['/', '\\', ':', '\0'])
+}
+
+/// 是否为只由安全的组成部分构成的相对路径 (分隔符可以是 "/" 或 "\")。
+pub fn is_safe_relative_path(value: &str) -> bool {
+    value.split(['/', '\\']).all(is_safe_component)
+}
+
+/// 启动时使用的清单：设置了镜像地址且已启用过镜像上的清单时使用该清单，
+/// 否则使用配置目录下的清单或内置清单。
+pub fn load_active(config_dir: &Path, mirror_url: &str) -> EngineManifest {
+    let mirror_path = config_dir.join(MIRROR_MANIFEST_FILE_NAME);
+    if !mirror_url.trim().is_empty() && mirror_path.exists() {
+        return EngineManifest::load(mirror_path);
+    }
+    EngineManifest::load(config_dir.join(MANIFEST_FILE_NAME))
+}
+
+/// 设置了镜像地址时，从镜像获取最新的清单并启用 (保存到配置目录并替换当前使用的清单)。
+/// 下载或更新引擎前调用；检查更新只比较版本，不启用镜像上的清单。
+///
+/// 没有安装记录的旧版安装按当前清单中的位置记录下来，换用镜像上的清单后仍能找到已安装的引擎。
+pub async fn apply_mirror(app: &AppHandle) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let mirror_url = state.settings.lock().unwrap().engine_manifest_url.trim().to_string();
+    if mirror_url.is_empty() {
+        return Ok(());
+    }
+    let manifest = EngineManifest::fetch(&mirror_url).await?;
+    let config_dir = app.path_resolver().app_config_dir()
+        .ok_or_else(|| AppError::DirUnavailable { dir: "config".to_string() })?;
+    manifest.save(&config_dir.join(MIRROR_MANIFEST_FILE_NAME))?;
+
+    let local_data_dir = local_data_dir(app)?;
+    let current = state.engine_manifest.lock().unwrap().clone();
+    let mut installs = state.engine_installs.lock().unwrap();
+    for engine in &current.engines {
+        if installs.get(&engine.id).is_none() && engine.entry_path(&local_data_dir).exists() {
+            let legacy = InstalledVersion { version: String::new(), dir: engine.install_dir.clone(), entry: engine.entry.clone(), installed_at: 0 };
+            installs.set(&engine.id, Some(InstalledEngine { current: legacy, previous: None }))?;
+        }
+    }
+    drop(installs);
+    println!("[MANIFEST] 已启用镜像上的引擎清单");
+    *state.engine_manifest.lock().unwrap() = manifest;
+    Ok(())
+}
+
+/// 清空镜像地址后删除保存的镜像清单，恢复使用配置目录下的清单或内置清单。
+pub fn remove_mirror(app: &AppHandle) -> Result<(), AppError> {
+    let config_dir = app.path_resolver().app_config_dir()
+        .ok_or_else(|| AppError::DirUnavailable { dir: "config".to_string() })?;
+    let mirror_path = config_dir.join(MIRROR_MANIFEST_FILE_NAME);
+    if mirror_path.exists() {
+        println!("[MANIFEST] 删除镜像引擎清单: {:?}", mirror_path);
+        fs::remove_file(&mirror_path)?;
+    }
+    *app.state::<AppState>().engine_manifest.lock().unwrap() = load_active(&config_dir, "");
+    Ok(())
+}
+
+/// 当前平台的名称，格式为 "<操作系统>-<架构>"，例如 "windows-x86_64"。
+pub fn current_platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// 取出清单中指定引擎的配置。
+pub fn engine_spec(app: &AppHandle, id: &str) -> Result<EngineSpec, AppError> {
+    app.state::<AppState>().engine_manifest.lock().unwrap()
+        .engine(id)
+        .cloned()
+        .ok_or_else(|| AppError::UnknownEngine { engine: id.to_string() })
+}
+
+/// 本地数据目录，引擎与安装记录均保存在其中。
+pub fn local_data_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    app.path_resolver().app_local_data_dir().ok_or_else(|| AppError::DirUnavailable { dir: "local_data".to_string() })
+}
+
+/// 引擎入口可执行文件的完整路径：已记录安装版本时使用当前版本，否则使用清单中的安装目录。
+pub fn entry_path(app: &AppHandle, id: &str) -> Result<PathBuf, AppError> {
+    let local_data_dir = local_data_dir(app)?;
+    if let Some(installed) = app.state::<AppState>().engine_installs.lock().unwrap().get(id) {
+        return Ok(installed.current.entry_path(&local_data_dir));
+    }
+    Ok(engine_spec(app, id)?.entry_path(&local_data_dir))
+}
+
+/// `latest` 是否比 `installed` 新。按点分隔的数字逐段比较 (忽略前缀 "v")，无法解析时只要不同即视为新版本。
+pub fn is_newer_version(latest: &str, installed: &str) -> bool {
+    let parse = |v: &str| -> Option<Vec<u64>> {
+        let mut parts: Vec<u64> = v.trim().trim_start_matches(['v', 'V']).split('.').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+        // "0.2" 与 "0.2.0" 视为同一版本
+        while parts.len() > 1 && parts.last() == Some(&0) {
+            parts.pop();
+        }
+        Some(parts)
+    };
+    match (parse(latest), parse(installed)) {
+        (Some(latest), Some(installed)) => latest > installed,
+        _ => latest.trim() != installed.trim(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_versions_are_compared_numerically() {
+        assert!(is_newer_version("0.2.10", "0.2.9"));
+        assert!(is_newer_version("1.0", "0.9.9"));
+        assert!(is_newer_version("v0.3.0", "0.2.0.7"));
+        assert!(!is_newer_version("0.2.0.7", "0.2.0.7"));
+        assert!(!is_newer_version("0.2.9", "0.2.10"));
+    }
+
+    #[test]
+    fn trailing_zeros_do_not_make_a_version_newer() {
+        assert!(!is_newer_version("0.2.0", "0.2"));
+        assert!(!is_newer_version("0.2", "v0.2.0"));
+    }
+
+    fn spec(id: &str, version: &str, install_dir: &str, entry: &str) -> EngineSpec {
+        EngineSpec {
+            id: id.to_string(),
+            name: id.to_string(),
+            version: version.to_string(),
+            install_dir: install_dir.to_string(),
+            entry: entry.to_string(),
+            args: Vec::new(),
+            ready_marker: None,
+            artifacts: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn builtin_manifest_is_valid() {
+        // 内置清单同样要求登记每个安装包的校验值，否则内置引擎无法下载安装
+        let manifest = EngineManifest::default();
+        for engine in &manifest.engines {
+            for artifact in engine.artifacts.values().flatten() {
+                assert!(artifact.has_checksum(), "{} / {} 没有登记 SHA-256", engine.id, artifact.id);
+            }
+        }
+        assert!(manifest.validate().is_ok());
+    }
+
+    #[test]
+    fn relative_paths_inside_the_data_dir_are_accepted() {
+        assert!(spec("rapidocr", "0.2.0", "", "RapidOCR-json_v0.2.0/RapidOCR-json.exe").validate().is_ok());
+        assert!(spec("translator", "v0.2.0-beta", "engines\\legacy", "translate_engine.exe").validate().is_ok());
+    }
+
+    #[test]
+    fn paths_escaping_the_data_dir_are_rejected() {
+        for (id, version, install_dir, entry) in [
+            ("../../..", "0.2.0", "", "engine.exe"),
+            ("translator", "..", "", "engine.exe"),
+            ("translator", "0.2/../..", "", "engine.exe"),
+            ("translator", "", "", "engine.exe"),
+            ("translator", "0.2.0", "C:\\Windows", "engine.exe"),
+            ("translator", "0.2.0", "/usr/bin", "engine.exe"),
+            ("translator", "0.2.0", "engines/../..", "engine.exe"),
+            ("translator", "0.2.0", "", "../engine.exe"),
+            ("translator", "0.2.0", "", "/tmp/engine.exe"),
+            ("translator", "0.2.0", "", ""),
+        ] {
+            assert!(spec(id, version, install_dir, entry).validate().is_err(), "{id} {version} {install_dir} {entry}");
+        }
+    }
+
+    #[test]
+    fn artifact_file_names_must_be_single_components() {
+        let mut engine = spec("translator", "0.2.0", "", "translate_engine.exe");
+        let artifact = |file_name: Option<&str>, url: &str| Artifact {
+            id: "engine".to_string(),
+            url: url.to_string(),
+            archive: ArchiveType::File,
+            file_name: file_name.map(str::to_string),
+            sha256: Some("0".repeat(64)),
+            size: None,
+        };
+        engine.artifacts.insert("windows-x86_64".to_string(), vec![artifact(Some("translate_engine.exe"), "https://example.com/a.exe")]);
+        assert!(engine.validate().is_ok());
+        engine.artifacts.insert("windows-x86_64".to_string(), vec![artifact(Some("..\\evil.exe"), "https://example.com/a.exe")]);
+        assert!(engine.validate().is_err());
+        engine.artifacts.insert("windows-x86_64".to_string(), vec![artifact(None, "https://example.com/..")]);
+        assert!(engine.validate().is_err());
+    }
+
+    #[test]
+    fn artifacts_without_checksum_are_rejected() {
+        let mut engine = spec("rapidocr", "0.2.0", "", "RapidOCR-json.exe");
+        let artifact = |sha256: Option<&str>| Artifact {
+            id: "ocr".to_string(),
+            url: "https://example.com/ocr.7z".to_string(),
+            archive: ArchiveType::SevenZ,
+            file_name: None,
+            sha256: sha256.map(str::to_string),
+            size: None,
+        };
+        engine.artifacts.insert("windows-x86_64".to_string(), vec![artifact(Some(&"a".repeat(64)))]);
+        assert!(engine.validate().is_ok());
+        engine.artifacts.insert("windows-x86_64".to_string(), vec![artifact(None)]);
+        assert!(matches!(engine.validate(), Err(AppError::ManifestInvalid { .. })));
+        engine.artifacts.insert("windows-x86_64".to_string(), vec![artifact(Some(" "))]);
+        assert!(engine.validate().is_err());
+    }
+
+    #[test]
+    fn unparsable_versions_differ_when_not_equal() {
+        assert!(is_newer_version("2024-05-beta", "2024-04-beta"));
+        assert!(is_newer_version("1.0", ""));
+        assert!(!is_newer_version(" nightly ", "nightly"));
+    }
+}
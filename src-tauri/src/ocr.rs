@@ -0,0 +1,351 @@
+// 文件: src-tauri\src\ocr.rs
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::AppError;
+use crate::i18n::t;
+use crate::manifest;
+use crate::settings::{AppSettings, AppState, CustomOcrEngine};
+use crate::worker::{escape_non_ascii, run_with_timeout, WorkerSpec};
+use tokio::process::Command;
+
+// --- 常量定义 ---
+// 内置 OCR 引擎 (RapidOCR)
+// 安装目录、可执行文件与启动参数由引擎清单 (manifest) 提供
+pub const RAPIDOCR_ENGINE_ID: &str = "rapidocr";
+// 自定义 JSON 协议引擎初始化完成时输出的标志 (PaddleOCR-json 与 RapidOCR-json 相同)
+const JSON_OCR_READY_MARKER: &str = "OCR init completed.";
+
+// Tesseract 命令行
+pub const TESSERACT_ENGINE_ID: &str = "tesseract";
+
+/// 供设置界面展示的引擎信息。
+#[derive(Clone, Debug, Serialize)]
+pub struct OcrEngineInfo {
+    pub id: String,
+    pub name: String,
+}
+
+/// 一行识别结果。
+///
+/// `bbox` 为文本框四个角点的像素坐标，顺序为左上、右上、右下、左下；
+/// `score` 为引擎给出的置信度，范围 0.0 ~ 1.0。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OcrLine {
+    pub text: String,
+    #[serde(rename = "box")]
+    pub bbox: [[i32; 2]; 4],
+    pub score: f32,
+}
+
+impl OcrLine {
+    pub fn left(&self) -> i32 {
+        self.bbox.iter().map(|p| p[0]).min().unwrap_or(0)
+    }
+
+    pub fn right(&self) -> i32 {
+        self.bbox.iter().map(|p| p[0]).max().unwrap_or(0)
+    }
+
+    pub fn top(&self) -> i32 {
+        self.bbox.iter().map(|p| p[1]).min().unwrap_or(0)
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.bbox.iter().map(|p| p[1]).max().unwrap_or(0)
+    }
+
+    pub fn width(&self) -> i32 {
+        self.right() - self.left()
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom() - self.top()
+    }
+
+    /// 是否为由单个非字母数字字符组成的行（通常来自图标、边框等）。
+    fn is_single_symbol(&self) -> bool {
+        let mut chars = self.text.trim().chars();
+        matches!((chars.next(), chars.next()), (Some(c), None) if !c.is_alphanumeric())
+    }
+}
+
+/// 一次识别的完整结果，文本行按引擎输出的顺序排列。
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct OcrResult {
+    pub blocks: Vec<OcrLine>,
+}
+
+impl OcrResult {
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|line| line.text.trim().is_empty())
+    }
+
+    /// 按设置过滤噪声行：低置信度、文本框过小以及单个符号的行。
+    pub fn filter_noise(&mut self, settings: &AppSettings) {
+        let before = self.blocks.len();
+        self.blocks.retain(|line| {
+            let keep = !line.text.trim().is_empty()
+                && line.score >= settings.ocr_min_score
+                // 引擎未返回文本框时 (全为 0) 不做尺寸过滤
+                && (line.bbox == [[0; 2]; 4] || line.width().min(line.height()) >= settings.ocr_min_box_size)
+                && !(settings.ocr_drop_single_symbols && line.is_single_symbol());
+            if !keep {
+                println!("[OCR] 过滤噪声行: '{}' (置信度={:.2}, 尺寸={}x{})", line.text, line.score, line.width(), line.height());
+            }
+            keep
+        });
+        println!("[OCR] 噪声过滤: {} 行 -> {} 行", before, self.blocks.len());
+    }
+}
+
+#[async_trait::async_trait]
+pub trait OcrEngine {
+    /// 识别图片中的文字，返回带文本框与置信度的结构化结果。
+    async fn recognize(&self, image_path: &str) -> Result<OcrResult, AppError>;
+}
+
+/// 内置的 RapidOCR-json 引擎，使用常驻进程。
+pub struct RapidOcrEngine {
+    app_handle: AppHandle,
+    timeout: Duration,
+}
+
+impl RapidOcrEngine {
+    pub fn new(app_handle: AppHandle, timeout: Duration) -> Self {
+        Self { app_handle, timeout }
+    }
+}
+
+#[async_trait::async_trait]
+impl OcrEngine for RapidOcrEngine {
+    async fn recognize(&self, image_path: &str) -> Result<OcrResult, AppError> {
+        let engine = manifest::engine_spec(&self.app_handle, RAPIDOCR_ENGINE_ID)?;
+        let ocr_exe_path = manifest::entry_path(&self.app_handle, RAPIDOCR_ENGINE_ID)?;
+        println!("[OCR] 预期的 OCR 执行文件路径: {:?}", ocr_exe_path);
+
+        if !ocr_exe_path.exists() {
+            return Err(AppError::EngineMissing { engine: RAPIDOCR_ENGINE_ID.to_string() });
+        }
+
+        let spec = WorkerSpec {
+            tag: RAPIDOCR_ENGINE_ID,
+            exe_path: &ocr_exe_path,
+            args: &engine.args.iter().map(String::as_str).collect::<Vec<_>>(),
+            ready_marker: engine.ready_marker.as_deref(),
+            timeout: self.timeout,
+        };
+        request_json_engine(&self.app_handle, RAPIDOCR_ENGINE_ID, &spec, image_path).await
+    }
+}
+
+/// 用户配置的、与 RapidOCR-json 使用相同 JSON 协议的可执行文件 (如 PaddleOCR-json)。
+pub struct JsonProtocolOcrEngine {
+    app_handle: AppHandle,
+    config: CustomOcrEngine,
+    timeout: Duration,
+}
+
+impl JsonProtocolOcrEngine {
+    pub fn new(app_handle: AppHandle, config: CustomOcrEngine, timeout: Duration) -> Self {
+        Self { app_handle, config, timeout }
+    }
+}
+
+#[async_trait::async_trait]
+impl OcrEngine for JsonProtocolOcrEngine {
+    async fn recognize(&self, image_path: &str) -> Result<OcrResult, AppError> {
+        let exe_path = PathBuf::from(&self.config.exe_path);
+        if !exe_path.exists() {
+            println!("[OCR] 找不到OCR引擎 '{}': {:?}", self.config.name, exe_path);
+            return Err(AppError::EngineMissing { engine: self.config.name.clone() });
+        }
+
+        let args: Vec<&str> = self.config.args.iter().map(String::as_str).collect();
+        let spec = WorkerSpec {
+            tag: &self.config.id,
+            exe_path: &exe_path,
+            args: &args,
+            ready_marker: Some(self.config.ready_marker.as_deref().unwrap_or(JSON_OCR_READY_MARKER)),
+            timeout: self.timeout,
+        };
+        request_json_engine(&self.app_handle, &self.config.id, &spec, image_path).await
+    }
+}
+
+/// Tesseract 命令行引擎，每次识别启动一个进程。
+pub struct TesseractEngine {
+    exe_path: String,
+    lang: String,
+    timeout: Duration,
+}
+
+impl TesseractEngine {
+    pub fn new(exe_path: &str, ocr_lang: &str, timeout: Duration) -> Self {
+        let exe_path = if exe_path.trim().is_empty() { "tesseract" } else { exe_path.trim() };
+        Self { exe_path: exe_path.to_string(), lang: tesseract_lang(ocr_lang).to_string(), timeout }
+    }
+}
+
+#[async_trait::async_trait]
+impl OcrEngine for TesseractEngine {
+    async fn recognize(&self, image_path: &str) -> Result<OcrResult, AppError> {
+        println!("[OCR] 调用 Tesseract: 路径='{}', 语言='{}'", self.exe_path, self.lang);
+
+        // 使用 TSV 输出以获取每个单词的位置与置信度
+        let mut command = Command::new(&self.exe_path);
+        command.args([image_path, "stdout", "-l", self.lang.as_str(), "tsv"]);
+        #[cfg(windows)]
+        command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+        let output = run_with_timeout(command, TESSERACT_ENGINE_ID, self.timeout).await?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            return Err(AppError::EngineError { engine: TESSERACT_ENGINE_ID.to_string(), detail: stderr });
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        Ok(parse_tesseract_tsv(&stdout))
+    }
+}
+
+/// 将 Tesseract 的 TSV 输出按行合并。
+///
+/// TSV 列依次为: level, page_num, block_num, par_num, line_num, word_num,
+/// left, top, width, height, conf, text。只有 level 为 5 的记录是单词。
+fn parse_tesseract_tsv(tsv: &str) -> OcrResult {
+    let mut blocks: Vec<OcrLine> = Vec::new();
+    let mut current_key = None;
+    let mut confidences: Vec<f32> = Vec::new();
+
+    for row in tsv.lines().skip(1) {
+        let cols: Vec<&str> = row.split('\t').collect();
+        if cols.len() < 12 || cols[0] != "5" {
+            continue;
+        }
+        let text = cols[11].trim();
+        if text.is_empty() {
+            continue;
+        }
+        let num = |i: usize| cols[i].parse::<i32>().unwrap_or(0);
+        let (left, top, width, height) = (num(6), num(7), num(8), num(9));
+        let conf = cols[10].parse::<f32>().unwrap_or(0.0).max(0.0) / 100.0;
+        let key = (num(1), num(2), num(3), num(4));
+
+        match blocks.last_mut() {
+            Some(line) if current_key == Some(key) => {
+                // 同一行的后续单词：追加文本并扩展文本框
+                line.text.push(' ');
+                line.text.push_str(text);
+                let (l, t) = (line.left().min(left), line.top().min(top));
+                let (r, b) = (line.right().max(left + width), line.bottom().max(top + height));
+                line.bbox = [[l, t], [r, t], [r, b], [l, b]];
+                confidences.push(conf);
+                line.score = confidences.iter().sum::<f32>() / confidences.len() as f32;
+            }
+            _ => {
+                current_key = Some(key);
+                confidences = vec![conf];
+                blocks.push(OcrLine {
+                    text: text.to_string(),
+                    bbox: [[left, top], [left + width, top], [left + width, top + height], [left, top + height]],
+                    score: conf,
+                });
+            }
+        }
+    }
+    OcrResult { blocks }
+}
+
+/// 通过常驻进程发送一次 JSON 协议的识别请求，并解析结果。
+async fn request_json_engine(app: &AppHandle, engine_id: &str, spec: &WorkerSpec<'_>, image_path: &str) -> Result<OcrResult, AppError> {
+    let request = escape_non_ascii(&serde_json::json!({ "image_path": image_path }).to_string());
+    println!("[OCR] 发送识别请求到引擎 '{}': {}", engine_id, request);
+
+    let worker = app.state::<AppState>().engine_workers.get(engine_id);
+    let stdout = worker.request(spec, &request).await?;
+    println!("[OCR] Stdout (decoded): '{}'", stdout);
+
+    parse_json_protocol_response(engine_id, &stdout)
+}
+
+/// 解析 RapidOCR-json / PaddleOCR-json 协议的输出。
+///
+/// `code == 100` 表示成功，`data` 为 `{ text, box, score }` 数组；`code == 101` 表示没有识别到文字；
+/// 其他情况下 `data` 为错误信息。
+fn parse_json_protocol_response(engine_id: &str, stdout: &str) -> Result<OcrResult, AppError> {
+    let ocr_value: serde_json::Value = serde_json::from_str(stdout)
+        .map_err(|e| AppError::ParseFailure { source: engine_id.to_string(), detail: e.to_string() })?;
+
+    if ocr_value["code"].as_i64().unwrap_or(0) == 100 {
+        let blocks = ocr_value["data"].as_array().unwrap_or(&vec![]).iter()
+            .filter_map(|item| {
+                let text = item["text"].as_str()?.to_string();
+                let mut bbox = [[0i32; 2]; 4];
+                if let Some(points) = item["box"].as_array() {
+                    for (corner, point) in bbox.iter_mut().zip(points) {
+                        corner[0] = point[0].as_f64().unwrap_or(0.0).round() as i32;
+                        corner[1] = point[1].as_f64().unwrap_or(0.0).round() as i32;
+                    }
+                }
+                let score = item["score"].as_f64().unwrap_or(1.0) as f32;
+                Some(OcrLine { text, bbox, score })
+            })
+            .collect();
+        Ok(OcrResult { blocks })
+    } else if ocr_value["code"].as_i64() == Some(101) {
+        // 101: 图片中没有文字
+        Err(AppError::NoTextFound)
+    } else {
+        Err(AppError::EngineError {
+            engine: engine_id.to_string(),
+            detail: ocr_value["data"].as_str().map_or_else(|| t("detail.unknown_error"), str::to_string),
+        })
+    }
+}
+
+/// 将设置中的语言代码转换为 Tesseract 的语言包名称。
+fn tesseract_lang(ocr_lang: &str) -> &str {
+    match ocr_lang {
+        "auto" => "chi_sim+eng",
+        "zh" => "chi_sim",
+        "en" => "eng",
+        "ja" => "jpn",
+        "ko" => "kor",
+        "de" => "deu",
+        "fr" => "fra",
+        other => other,
+    }
+}
+
+/// 列出所有可用的 OCR 引擎 (内置引擎 + 用户配置的引擎)。
+pub fn available_engines(settings: &AppSettings) -> Vec<OcrEngineInfo> {
+    let mut engines = vec![
+        OcrEngineInfo { id: RAPIDOCR_ENGINE_ID.to_string(), name: t("name.rapidocr_builtin") },
+        OcrEngineInfo { id: TESSERACT_ENGINE_ID.to_string(), name: "Tesseract".to_string() },
+    ];
+    engines.extend(settings.custom_ocr_engines.iter().map(|e| OcrEngineInfo {
+        id: e.id.clone(),
+        name: e.name.clone(),
+    }));
+    engines
+}
+
+/// 根据设置选择 OCR 引擎：优先使用为当前识别语言单独指定的引擎，否则使用默认引擎。
+pub fn get_ocr_engine(app: &AppHandle, settings: &AppSettings) -> Result<Box<dyn OcrEngine + Send + Sync>, AppError> {
+    let engine_id = settings.ocr_engine_by_lang.get(&settings.ocr_lang)
+        .unwrap_or(&settings.ocr_engine);
+    println!("[OCR] 识别语言='{}', 使用引擎='{}'", settings.ocr_lang, engine_id);
+
+    match engine_id.as_str() {
+        RAPIDOCR_ENGINE_ID => Ok(Box::new(RapidOcrEngine::new(app.clone(), settings.engine_timeout()))),
+        TESSERACT_ENGINE_ID => Ok(Box::new(TesseractEngine::new(&settings.tesseract_path, &settings.ocr_lang, settings.engine_timeout()))),
+        id => settings.custom_ocr_engines.iter()
+            .find(|e| e.id == id)
+            .map(|config| Box::new(JsonProtocolOcrEngine::new(app.clone(), config.clone(), settings.engine_timeout())) as Box<dyn OcrEngine + Send + Sync>)
+            .ok_or_else(|| AppError::UnknownEngine { engine: id.to_string() }),
+    }
+}
@@ -0,0 +1,261 @@
+// 文件: src-tauri\src\segmenter.rs
+//
+// 长文本分段翻译。
+// 整页 OCR 文本一次性传给翻译引擎时，会超出 Windows 命令行长度限制或模型的上下文长度。
+// 这里先按段落、再按句子将文本切分为不超过指定长度的片段，翻译后按原有的段落分隔重新拼接。
+
+use std::ops::Range;
+
+use crate::glossary;
+
+/// 待翻译的片段，以及拼接译文时跟在它后面的分隔符。
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub text: String,
+    separator: Separator,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Separator {
+    // 同一段落内相邻的句子，记录原文中两句之间是否换行
+    Sentence { line_break: bool },
+    // 段落之间，保存原文中的换行数
+    Paragraph(usize),
+    // 最后一个片段
+    End,
+}
+
+/// 将文本切分为长度（字符数）不超过 `max_chars` 的片段。
+///
+/// 文本本身不超过 `max_chars` 时不做切分，保持整体翻译的效果。
+pub fn split_into_chunks(text: &str, max_chars: usize) -> Vec<Chunk> {
+    let max_chars = max_chars.max(1);
+    if text.chars().count() <= max_chars {
+        return vec![Chunk { text: text.to_string(), separator: Separator::End }];
+    }
+
+    let mut chunks = Vec::new();
+    for (paragraph, newlines) in split_paragraphs(text) {
+        // 句子均为段落的子串，相邻的句子直接取原文中的连续区间合并，保留其间的空白与换行
+        let mut current: Option<(usize, usize)> = None;
+        for sentence in split_sentences(paragraph) {
+            for piece in hard_split(&paragraph[sentence.clone()], max_chars) {
+                let start = sentence.start + piece.start;
+                let end = sentence.start + piece.end;
+                current = match current {
+                    Some((chunk_start, _)) if paragraph[chunk_start..end].chars().count() <= max_chars => Some((chunk_start, end)),
+                    Some((chunk_start, chunk_end)) => {
+                        let line_break = paragraph[chunk_end..start].contains('\n');
+                        chunks.push(Chunk { text: paragraph[chunk_start..chunk_end].to_string(), separator: Separator::Sentence { line_break } });
+                        Some((start, end))
+                    }
+                    None => Some((start, end)),
+                };
+            }
+        }
+        if let Some((start, end)) = current {
+            chunks.push(Chunk { text: paragraph[start..end].to_string(), separator: Separator::Paragraph(newlines) });
+        }
+    }
+    if let Some(last) = chunks.last_mut() {
+        last.separator = Separator::End;
+    }
+    chunks
+}
+
+/// 将各片段的译文按原文的段落结构拼接起来。`translated` 与 `chunks` 一一对应。
+pub fn reassemble(chunks: &[Chunk], translated: &[String], target_lang: &str) -> String {
+    // 中文、日文的句子之间不加空格
+    let sentence_separator = if matches!(target_lang, "zh" | "ja") { "" } else { " " };
+    let mut out = String::new();
+    for (chunk, text) in chunks.iter().zip(translated) {
+        out.push_str(text.trim());
+        match chunk.separator {
+            Separator::Sentence { line_break: true } => out.push('\n'),
+            Separator::Sentence { line_break: false } => out.push_str(sentence_separator),
+            Separator::Paragraph(newlines) => out.push_str(&"\n".repeat(newlines)),
+            Separator::End => {}
+        }
+    }
+    out
+}
+
+/// 按换行切分段落，返回 (段落, 其后的换行数)。
+///
+/// 文本中存在空行时（保留原文换行的模式），只在空行处分段，段落内的单个换行保留在段落中。
+fn split_paragraphs(text: &str) -> Vec<(&str, usize)> {
+    let min_newlines = if text.contains("\n\n") { 2 } else { 1 };
+    let mut paragraphs = Vec::new();
+    let mut start = 0;
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\n' {
+            let run_start = i;
+            while i < bytes.len() && (bytes[i] == b'\n' || bytes[i] == b'\r') {
+                i += 1;
+            }
+            let newlines = text[run_start..i].matches('\n').count();
+            if newlines >= min_newlines {
+                let paragraph = text[start..run_start].trim();
+                if !paragraph.is_empty() {
+                    paragraphs.push((paragraph, newlines));
+                }
+                start = i;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    let paragraph = text[start..].trim();
+    if !paragraph.is_empty() {
+        paragraphs.push((paragraph, min_newlines));
+    }
+    paragraphs
+}
+
+/// 在句末标点处切分句子，返回各句在段落中的字节区间（不含首尾空白）。
+/// 拉丁文字的句号后需跟空白，避免切开 "3.14"、"v1.2" 之类的写法。
+fn split_sentences(paragraph: &str) -> Vec<Range<usize>> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = paragraph.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let end = i + c.len_utf8();
+        let is_end = match c {
+            '。' | '！' | '？' | '…' => true,
+            '.' | '!' | '?' | ';' => chars.peek().is_none_or(|(_, next)| next.is_whitespace()),
+            _ => false,
+        };
+        if is_end {
+            let sentence = trimmed(paragraph, start..end);
+            if !sentence.is_empty() {
+                sentences.push(sentence);
+            }
+            start = end;
+        }
+    }
+    let rest = trimmed(paragraph, start..paragraph.len());
+    if !rest.is_empty() {
+        sentences.push(rest);
+    }
+    sentences
+}
+
+/// 将超长的句子强制切分，尽量在空白处断开。返回各片段在句子中的字节区间。
+/// 术语占位符不可拆分，断点落在占位符中间时移到占位符之前 (占位符位于开头时移到其后)。
+fn hard_split(sentence: &str, max_chars: usize) -> Vec<Range<usize>> {
+    let mut pieces = Vec::new();
+    let mut pos = 0;
+    while sentence[pos..].chars().count() > max_chars {
+        let rest = &sentence[pos..];
+        let limit = rest.char_indices().nth(max_chars).map_or(rest.len(), |(i, _)| i);
+        let mut cut = rest[..limit].rfind(char::is_whitespace).filter(|i| *i > 0).unwrap_or(limit);
+        if let Some(token) = glossary::placeholder_ranges(rest).into_iter().find(|r| r.start < cut && cut < r.end) {
+            cut = if token.start > 0 { token.start } else { token.end };
+        }
+        pieces.push(trimmed(sentence, pos..pos + cut));
+        pos = trimmed(sentence, pos + cut..sentence.len()).start;
+    }
+    if pos < sentence.len() {
+        pieces.push(pos..sentence.len());
+    }
+    pieces
+}
+
+/// 去掉区间首尾的空白后的字节区间。
+fn trimmed(text: &str, range: Range<usize>) -> Range<usize> {
+    let slice = &text[range.clone()];
+    let start = range.start + (slice.len() - slice.trim_start().len());
+    let end = range.start + slice.trim_end().len();
+    start..end.max(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(chunks: &[Chunk]) -> Vec<&str> {
+        chunks.iter().map(|c| c.text.as_str()).collect()
+    }
+
+    fn identity(chunks: &[Chunk]) -> Vec<String> {
+        chunks.iter().map(|c| c.text.clone()).collect()
+    }
+
+    #[test]
+    fn short_text_is_kept_whole() {
+        let chunks = split_into_chunks("Hello.\n\nWorld.", 100);
+        assert_eq!(texts(&chunks), ["Hello.\n\nWorld."]);
+        assert_eq!(reassemble(&chunks, &identity(&chunks), "en"), "Hello.\n\nWorld.");
+    }
+
+    #[test]
+    fn paragraph_breaks_are_preserved() {
+        let text = "First sentence. Second one.\n\nThird paragraph.\n\n\nLast.";
+        let chunks = split_into_chunks(text, 20);
+        assert_eq!(texts(&chunks), ["First sentence.", "Second one.", "Third paragraph.", "Last."]);
+        assert_eq!(reassemble(&chunks, &identity(&chunks), "en"), "First sentence. Second one.\n\nThird paragraph.\n\n\nLast.");
+    }
+
+    #[test]
+    fn line_breaks_inside_a_paragraph_are_preserved() {
+        let text = "Line one is here.\nLine two is here.\n\nNext paragraph.";
+        let chunks = split_into_chunks(text, 20);
+        assert_eq!(texts(&chunks), ["Line one is here.", "Line two is here.", "Next paragraph."]);
+        assert_eq!(reassemble(&chunks, &identity(&chunks), "en"), text);
+    }
+
+    #[test]
+    fn cjk_sentences_are_split_and_joined_without_spaces() {
+        let text = "今天天气很好。我们去公园吧！你觉得呢？";
+        let chunks = split_into_chunks(text, 8);
+        assert_eq!(texts(&chunks), ["今天天气很好。", "我们去公园吧！", "你觉得呢？"]);
+        assert_eq!(reassemble(&chunks, &identity(&chunks), "zh"), text);
+        assert_eq!(reassemble(&chunks, &identity(&chunks), "en"), "今天天气很好。 我们去公园吧！ 你觉得呢？");
+    }
+
+    #[test]
+    fn adjacent_sentences_are_merged_up_to_the_limit() {
+        let chunks = split_into_chunks("A b. C d. E f. G h.", 10);
+        assert_eq!(texts(&chunks), ["A b. C d.", "E f. G h."]);
+    }
+
+    #[test]
+    fn decimal_points_do_not_end_a_sentence() {
+        let ranges = split_sentences("Pi is 3.14 roughly. Version v1.2 is out.");
+        let paragraph = "Pi is 3.14 roughly. Version v1.2 is out.";
+        let sentences: Vec<&str> = ranges.into_iter().map(|r| &paragraph[r]).collect();
+        assert_eq!(sentences, ["Pi is 3.14 roughly.", "Version v1.2 is out."]);
+    }
+
+    #[test]
+    fn hard_split_prefers_whitespace() {
+        let sentence = "alpha beta gamma delta";
+        let pieces: Vec<&str> = hard_split(sentence, 12).into_iter().map(|r| &sentence[r]).collect();
+        assert_eq!(pieces, ["alpha beta", "gamma delta"]);
+    }
+
+    #[test]
+    fn hard_split_does_not_cut_through_placeholders() {
+        let sentence = "打开__TERM0__工具";
+        let pieces: Vec<&str> = hard_split(sentence, 4).into_iter().map(|r| &sentence[r]).collect();
+        assert_eq!(pieces, ["打开", "__TERM0__", "工具"]);
+
+        // 占位符恰好跨越片段边界
+        let sentence = "一二三__TERM12__四五";
+        let chunks = split_into_chunks(sentence, 5);
+        assert_eq!(texts(&chunks), ["一二三", "__TERM12__", "四五"]);
+        assert_eq!(reassemble(&chunks, &identity(&chunks), "zh"), sentence);
+    }
+
+    #[test]
+    fn hard_split_cuts_on_char_boundaries_without_whitespace() {
+        let sentence = "一二三四五六七八九十";
+        let pieces: Vec<&str> = hard_split(sentence, 4).into_iter().map(|r| &sentence[r]).collect();
+        assert_eq!(pieces, ["一二三四", "五六七八", "九十"]);
+
+        let chunks = split_into_chunks(sentence, 4);
+        assert_eq!(reassemble(&chunks, &identity(&chunks), "zh"), sentence);
+    }
+}
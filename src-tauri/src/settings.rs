@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tauri::{AppHandle, GlobalShortcutManager, PathResolver, State};
 use arboard::ImageData;
@@ -8,8 +10,19 @@ use image::ImageReader;
 use image::RgbaImage;
 use tauri::api::path as tauri_path;
 use std::sync::atomic::AtomicBool;
+use std::time::Duration;
 
-use crate::{register_global_shortcut, register_view_image_shortcut};
+use crate::{register_global_shortcut, register_view_image_shortcut, update_window_titles};
+use crate::error::AppError;
+use crate::glossary::{Glossary, GlossaryEntry};
+use crate::i18n;
+use crate::jobs::JobRegistry;
+use crate::installs::InstallRegistry;
+use crate::manifest::{self, EngineManifest};
+use crate::ocr::{OcrResult, RAPIDOCR_ENGINE_ID};
+use crate::translation_cache::TranslationCache;
+use crate::translator::{self, LanguagePair, Translator};
+use crate::worker::WorkerPool;
 
 //
 // 应用的全局共享状态
@@ -27,13 +40,35 @@ pub struct AppState {
     pub screenshot_history: Mutex<Vec<PathBuf>>,
     // --- 新增：当前查看的历史记录索引 (用于F3循环) ---
     pub history_index: Mutex<usize>,
+
+    // 常驻的引擎进程 (OCR 引擎按引擎 ID 区分，本地翻译引擎为 "translator")
+    pub engine_workers: WorkerPool,
+    // 不支持常驻模式的本地翻译引擎 (按可执行文件路径记录)，这些引擎直接使用单次模式
+    pub translator_daemon_unsupported: Mutex<HashSet<PathBuf>>,
+
+    // 翻译记忆缓存 (启动时从本地数据目录加载)
+    pub translation_cache: Mutex<TranslationCache>,
+    // 用户术语表 (启动时从配置目录加载)
+    pub glossary: Mutex<Glossary>,
+    // 正在执行的可取消任务 (截图识别、翻译)
+    pub jobs: JobRegistry,
+    // 本地引擎的版本与安装包信息 (启动时从配置目录加载，不存在时使用内置清单)
+    pub engine_manifest: Mutex<EngineManifest>,
+    // 已安装引擎的版本记录 (启动时从本地数据目录加载)
+    pub engine_installs: Mutex<InstallRegistry>,
 }
 
 // 缓存的结果结构
 #[derive(Clone, Debug, Serialize)]
 pub struct LastOcrResult {
+    // 产生该结果的任务 ID，结果窗口据此忽略其他任务推送的译文
+    pub job_id: u64,
     pub original_text: Option<String>,
     pub translated_text: Option<String>,
+    // 实际完成翻译的后端
+    pub translator_backend: Option<String>,
+    // 带文本框与置信度的结构化识别结果，供结果窗口高亮区域
+    pub ocr_result: Option<OcrResult>,
     pub image_path: String,
 }
 
@@ -42,12 +77,216 @@ pub struct AppSettings {
     pub shortcut: String,
     pub view_image_shortcut: String,
     pub target_lang: String,
+    // 翻译的源语言，"auto" 表示自动检测
+    #[serde(default = "default_source_lang")]
+    pub source_lang: String,
     pub preserve_line_breaks: bool,
     pub primary_action: String,
     #[serde(default)]
     pub enable_ocr: bool,
     #[serde(default)]
     pub enable_translation: bool,
+    // 截图范围: "cursor" 仅截取鼠标所在显示器, "all" 截取所有显示器拼接成的虚拟桌面
+    #[serde(default = "default_capture_scope")]
+    pub capture_scope: String,
+    // 默认使用的 OCR 引擎 ID
+    #[serde(default = "default_ocr_engine")]
+    pub ocr_engine: String,
+    // 待识别文字的语言，用于选择引擎及 Tesseract 的语言包
+    #[serde(default = "default_ocr_lang")]
+    pub ocr_lang: String,
+    // 按识别语言单独指定的 OCR 引擎 (语言代码 -> 引擎 ID)
+    #[serde(default)]
+    pub ocr_engine_by_lang: HashMap<String, String>,
+    // Tesseract 可执行文件路径，留空则从 PATH 中查找
+    #[serde(default)]
+    pub tesseract_path: String,
+    // 用户添加的、使用 RapidOCR-json 相同 JSON 协议的引擎 (如 PaddleOCR-json)
+    #[serde(default)]
+    pub custom_ocr_engines: Vec<CustomOcrEngine>,
+    // OCR 噪声过滤: 最低置信度 (0.0 ~ 1.0)
+    #[serde(default = "default_ocr_min_score")]
+    pub ocr_min_score: f32,
+    // OCR 噪声过滤: 文本框最短边的最小像素数
+    #[serde(default = "default_ocr_min_box_size")]
+    pub ocr_min_box_size: i32,
+    // OCR 噪声过滤: 是否丢弃只有单个符号的行
+    #[serde(default = "default_true")]
+    pub ocr_drop_single_symbols: bool,
+    // 翻译后端: "local" 本地翻译引擎, "llm" OpenAI 兼容的大模型接口, "libretranslate" LibreTranslate 服务
+    #[serde(default = "default_translator_backend")]
+    pub translator_backend: String,
+    // 主翻译后端失败时依次尝试的备用后端，取值同 translator_backend
+    #[serde(default)]
+    pub translator_fallbacks: Vec<String>,
+    // 本地翻译引擎是否以常驻进程运行
+    #[serde(default = "default_true")]
+    pub translator_daemon: bool,
+    // 是否启用翻译记忆缓存
+    #[serde(default = "default_true")]
+    pub translation_cache_enabled: bool,
+    // 翻译缓存最多保留的条目数，超出后淘汰最久未使用的条目
+    #[serde(default = "default_translation_cache_max_entries")]
+    pub translation_cache_max_entries: usize,
+    // 长文本分段翻译时每段的最大字符数
+    #[serde(default = "default_translation_chunk_size")]
+    pub translation_chunk_size: usize,
+    // 同时翻译的片段数，1 表示逐段依次翻译
+    #[serde(default = "default_translation_parallel_chunks")]
+    pub translation_parallel_chunks: usize,
+    // 单次 OCR / 本地翻译引擎调用的超时时间 (秒)，超时后终止引擎进程
+    #[serde(default = "default_engine_timeout_secs")]
+    pub engine_timeout_secs: u64,
+    // 引擎清单镜像地址，检查更新时从此处获取最新的引擎清单，留空则使用本地清单
+    #[serde(default)]
+    pub engine_manifest_url: String,
+    // 通知、错误提示与窗口标题使用的界面语言: "zh-CN" 或 "en-US"
+    #[serde(default = "default_ui_language")]
+    pub ui_language: String,
+    #[serde(default)]
+    pub llm: LlmTranslatorConfig,
+    #[serde(default)]
+    pub libretranslate: LibreTranslateConfig,
+}
+
+// OpenAI 兼容大模型翻译的配置
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LlmTranslatorConfig {
+    // chat completions 接口的完整地址
+    pub api_url: String,
+    pub api_key: String,
+    pub model: String,
+    pub temperature: f32,
+    // 是否以流式方式接收输出，结果窗口可实时显示生成中的译文
+    pub stream: bool,
+    // 提示词模板，支持 {source_lang}、{target_lang}、{text} 占位符
+    pub prompt_template: String,
+}
+
+impl Default for LlmTranslatorConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "http://localhost:11434/v1/chat/completions".to_string(),
+            api_key: String::new(),
+            model: "qwen2.5:7b".to_string(),
+            temperature: 0.2,
+            stream: true,
+            prompt_template: "Translate the following {source_lang} text into {target_lang}. \
+                Keep the original line breaks and output only the translation.\n\n{text}".to_string(),
+        }
+    }
+}
+
+// LibreTranslate 服务的配置
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LibreTranslateConfig {
+    // 服务根地址，例如 http://192.168.1.10:5000
+    pub api_url: String,
+    // 服务开启了 API Key 校验时填写
+    pub api_key: String,
+}
+
+impl Default for LibreTranslateConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "http://localhost:5000".to_string(),
+            api_key: String::new(),
+        }
+    }
+}
+
+// 日志中打印设置时隐藏 API Key
+fn redact(key: &str) -> &'static str {
+    if key.is_empty() { "" } else { "***" }
+}
+
+impl fmt::Debug for LlmTranslatorConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LlmTranslatorConfig")
+            .field("api_url", &self.api_url)
+            .field("api_key", &redact(&self.api_key))
+            .field("model", &self.model)
+            .field("temperature", &self.temperature)
+            .field("stream", &self.stream)
+            .field("prompt_template", &self.prompt_template)
+            .finish()
+    }
+}
+
+impl fmt::Debug for LibreTranslateConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LibreTranslateConfig")
+            .field("api_url", &self.api_url)
+            .field("api_key", &redact(&self.api_key))
+            .finish()
+    }
+}
+
+// 自定义 OCR 引擎配置
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CustomOcrEngine {
+    pub id: String,
+    pub name: String,
+    pub exe_path: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    // 引擎初始化完成时输出的标志行，默认为 "OCR init completed."
+    #[serde(default)]
+    pub ready_marker: Option<String>,
+}
+
+fn default_source_lang() -> String {
+    "auto".to_string()
+}
+
+fn default_capture_scope() -> String {
+    "cursor".to_string()
+}
+
+fn default_ocr_engine() -> String {
+    RAPIDOCR_ENGINE_ID.to_string()
+}
+
+fn default_ocr_lang() -> String {
+    "auto".to_string()
+}
+
+fn default_ocr_min_score() -> f32 {
+    0.5
+}
+
+fn default_ocr_min_box_size() -> i32 {
+    6
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_translator_backend() -> String {
+    "local".to_string()
+}
+
+fn default_translation_cache_max_entries() -> usize {
+    5000
+}
+
+fn default_translation_chunk_size() -> usize {
+    1000
+}
+
+fn default_translation_parallel_chunks() -> usize {
+    1
+}
+
+fn default_engine_timeout_secs() -> u64 {
+    60
+}
+
+fn default_ui_language() -> String {
+    "zh-CN".to_string()
 }
 
 impl Default for AppSettings {
@@ -56,10 +295,32 @@ impl Default for AppSettings {
             shortcut: "F1".to_string(),
             view_image_shortcut: "F3".to_string(),
             target_lang: "zh".to_string(),
+            source_lang: default_source_lang(),
             preserve_line_breaks: false,
             primary_action: "ocr".to_string(),
             enable_ocr: false,
             enable_translation: false,
+            capture_scope: default_capture_scope(),
+            ocr_engine: default_ocr_engine(),
+            ocr_lang: default_ocr_lang(),
+            ocr_engine_by_lang: HashMap::new(),
+            tesseract_path: String::new(),
+            custom_ocr_engines: Vec::new(),
+            ocr_min_score: default_ocr_min_score(),
+            ocr_min_box_size: default_ocr_min_box_size(),
+            ocr_drop_single_symbols: true,
+            translator_backend: default_translator_backend(),
+            translator_fallbacks: Vec::new(),
+            translator_daemon: true,
+            translation_cache_enabled: true,
+            translation_cache_max_entries: default_translation_cache_max_entries(),
+            translation_chunk_size: default_translation_chunk_size(),
+            translation_parallel_chunks: default_translation_parallel_chunks(),
+            engine_timeout_secs: default_engine_timeout_secs(),
+            engine_manifest_url: String::new(),
+            ui_language: default_ui_language(),
+            llm: LlmTranslatorConfig::default(),
+            libretranslate: LibreTranslateConfig::default(),
         }
     }
 }
@@ -88,53 +349,113 @@ impl AppSettings {
         fs::write(config_path, content)?;
         Ok(())
     }
+
+    pub fn engine_timeout(&self) -> Duration {
+        Duration::from_secs(self.engine_timeout_secs.max(1))
+    }
 }
 
 #[tauri::command]
-pub fn get_settings(state: State<AppState>) -> Result<AppSettings, String> {
+pub fn get_settings(state: State<AppState>) -> Result<AppSettings, AppError> {
     Ok(state.settings.lock().unwrap().clone())
 }
 
 #[tauri::command]
-pub async fn set_settings(app: AppHandle, state: State<'_, AppState>, settings: AppSettings) -> Result<(), String> {
+pub async fn set_settings(app: AppHandle, state: State<'_, AppState>, settings: AppSettings) -> Result<(), AppError> {
     println!("接收到新设置: {:?}", settings);
 
-    settings.save(&app.path_resolver()).map_err(|e| format!("保存设置文件失败: {}", e))?;
+    if !i18n::SUPPORTED_LANGUAGES.contains(&settings.ui_language.as_str()) {
+        return Err(AppError::UnsupportedUiLanguage { lang: settings.ui_language.clone() });
+    }
+
+    // 语言组合或翻译后端发生变化时，检查新的翻译后端是否支持
+    let lang_changed = {
+        let current = state.settings.lock().unwrap();
+        current.source_lang != settings.source_lang
+            || current.target_lang != settings.target_lang
+            || current.translator_backend != settings.translator_backend
+            || current.translator_fallbacks != settings.translator_fallbacks
+    };
+    if lang_changed {
+        // 获取不到语言列表时 (如 LibreTranslate 服务尚未配置或无法连接) 无从校验，直接保存
+        match translator::get_translator(&app, &settings).supported_pairs().await {
+            Ok(pairs) if !translator::is_pair_supported(&pairs, &settings.source_lang, &settings.target_lang) => {
+                return Err(AppError::UnsupportedPair { source: settings.source_lang.clone(), target: settings.target_lang.clone() });
+            }
+            Ok(_) => {}
+            Err(e) => println!("[SETTINGS] 无法获取翻译后端支持的语言组合，跳过校验: {}", e),
+        }
+    }
+
+    settings.save(&app.path_resolver()).map_err(|e| AppError::Io { detail: e.to_string() })?;
 
     let old_shortcut;
     let old_view_shortcut;
+    let old_ui_language;
+    let old_manifest_url;
     {
         let mut app_settings = state.settings.lock().unwrap();
         old_shortcut = app_settings.shortcut.clone();
         old_view_shortcut = app_settings.view_image_shortcut.clone();
+        old_ui_language = app_settings.ui_language.clone();
+        old_manifest_url = app_settings.engine_manifest_url.clone();
         *app_settings = settings.clone();
     }
 
+    if !old_manifest_url.trim().is_empty() && settings.engine_manifest_url.trim().is_empty() {
+        manifest::remove_mirror(&app)?;
+    }
+
+    if old_ui_language != settings.ui_language {
+        i18n::set_language(&settings.ui_language);
+        update_window_titles(&app);
+    }
+
     let mut shortcut_manager = app.global_shortcut_manager();
 
     if old_shortcut != settings.shortcut {
         let _ = shortcut_manager.unregister(&old_shortcut);
     }
     if let Err(e) = register_global_shortcut(app.clone(), &settings.shortcut) {
-        return Err(format!("注册主快捷键失败: {}", e));
+        return Err(AppError::ShortcutFailed { shortcut: settings.shortcut.clone(), detail: e.to_string() });
     }
 
     if old_view_shortcut != settings.view_image_shortcut {
         let _ = shortcut_manager.unregister(&old_view_shortcut);
     }
     if let Err(e) = register_view_image_shortcut(app.clone(), &settings.view_image_shortcut) {
-        return Err(format!("注册查看快捷键失败: {}", e));
+        return Err(AppError::ShortcutFailed { shortcut: settings.view_image_shortcut.clone(), detail: e.to_string() });
     }
 
     Ok(())
 }
 
 #[tauri::command]
-pub async fn copy_image_to_clipboard(path: String) -> Result<(), String> {
-    let img = ImageReader::open(path)
-        .map_err(|e| e.to_string())?
+pub fn get_glossary(state: State<AppState>) -> Vec<GlossaryEntry> {
+    state.glossary.lock().unwrap().entries().to_vec()
+}
+
+#[tauri::command]
+pub fn set_glossary(state: State<AppState>, entries: Vec<GlossaryEntry>) -> Result<(), AppError> {
+    println!("[GLOSSARY] 更新术语表，共 {} 条", entries.len());
+    state.glossary.lock().unwrap().set_entries(entries)
+}
+
+/// 从 CSV/TSV 文件导入术语。文件只有两列时，使用传入的源语言与目标语言。
+#[tauri::command]
+pub fn import_glossary(state: State<AppState>, path: String, source_lang: Option<String>, target_lang: Option<String>) -> Result<usize, AppError> {
+    let default_pair = match (source_lang, target_lang) {
+        (Some(source), Some(target)) if source != "auto" => Some(LanguagePair { source, target }),
+        _ => None,
+    };
+    state.glossary.lock().unwrap().import(Path::new(&path), default_pair.as_ref())
+}
+
+#[tauri::command]
+pub async fn copy_image_to_clipboard(path: String) -> Result<(), AppError> {
+    let img = ImageReader::open(path)?
         .decode()
-        .map_err(|e| e.to_string())?
+        .map_err(|e| AppError::ImageInvalid { detail: e.to_string() })?
         .to_rgba8();
 
     let image_data = ImageData {
@@ -143,18 +464,34 @@ pub async fn copy_image_to_clipboard(path: String) -> Result<(), String> {
         bytes: img.into_raw().into(),
     };
 
-    let mut clipboard = arboard::Clipboard::new().map_err(|e| e.to_string())?;
-    clipboard.set_image(image_data).map_err(|e| e.to_string())?;
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| AppError::Clipboard { detail: e.to_string() })?;
+    clipboard.set_image(image_data).map_err(|e| AppError::Clipboard { detail: e.to_string() })?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub async fn save_image_to_desktop(path: String) -> Result<(), String> {
-    let desktop_dir = tauri_path::desktop_dir().ok_or("无法获取桌面路径".to_string())?;
+pub async fn save_image_to_desktop(path: String) -> Result<(), AppError> {
+    let desktop_dir = tauri_path::desktop_dir().ok_or_else(|| AppError::DirUnavailable { dir: "desktop".to_string() })?;
     let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
     let new_filename = format!("screenshot-{}.png", timestamp);
     let dest_path = desktop_dir.join(new_filename);
-    fs::copy(&path, &dest_path).map_err(|e| format!("保存文件失败: {}", e))?;
+    fs::copy(&path, &dest_path)?;
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_output_hides_api_keys() {
+        let mut settings = AppSettings::default();
+        settings.llm.api_key = "sk-llm-secret".to_string();
+        settings.libretranslate.api_key = "libre-secret".to_string();
+        let printed = format!("{:?}", settings);
+        assert!(!printed.contains("sk-llm-secret"));
+        assert!(!printed.contains("libre-secret"));
+        assert!(printed.contains(r#"api_key: "***""#));
+    }
+}
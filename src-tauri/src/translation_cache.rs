@@ -0,0 +1,228 @@
+// 文件: src-tauri\src\translation_cache.rs
+//
+// 持久化的翻译记忆缓存。
+// 以 (规范化后的原文, 源语言, 目标语言, 翻译后端) 为键保存译文，
+// 反复截取相同的游戏菜单、对话框时无需重新翻译。缓存文件位于本地数据目录，超出条目上限时淘汰最久未使用的条目。
+// 查询与写入只修改内存中的数据，由后台线程定期 (以及程序退出时) 调用 `flush` 写入文件。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::AppError;
+use crate::settings::AppSettings;
+use crate::translator::LanguagePair;
+
+pub const CACHE_FILE_NAME: &str = "translation_cache.json";
+
+#[derive(Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    translated: String,
+    backend: String,
+    // 最近一次命中或写入的时间 (Unix 秒)，用于淘汰
+    last_used: u64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheFile {
+    entries: HashMap<String, CacheEntry>,
+    hits: u64,
+    misses: u64,
+}
+
+/// 提供给设置页面的缓存统计信息。
+#[derive(Serialize, Clone, Debug)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub file_size: u64,
+}
+
+#[derive(Default)]
+pub struct TranslationCache {
+    // 为 None 时缓存尚未加载（或无法获取数据目录），所有操作均不生效
+    path: Option<PathBuf>,
+    data: CacheFile,
+    // 内存中的数据是否有尚未写入文件的修改
+    dirty: bool,
+}
+
+impl TranslationCache {
+    /// 从缓存文件加载，文件不存在或损坏时从空缓存开始。
+    pub fn load(path: PathBuf) -> Self {
+        let data = fs::read_to_string(&path).ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { path: Some(path), data, dirty: false }
+    }
+
+    /// 按后端顺序查找缓存，返回 (译文, 翻译后端)。`backends` 为各后端的 `backend_key`。
+    pub fn get(&mut self, text: &str, pair: &LanguagePair, backends: &[String]) -> Option<(String, String)> {
+        self.path.as_ref()?;
+        let now = now_secs();
+        let found = backends.iter().find_map(|backend| {
+            let entry = self.data.entries.get_mut(&cache_key(text, pair, backend))?;
+            entry.last_used = now;
+            Some((entry.translated.clone(), entry.backend.clone()))
+        });
+        if found.is_some() {
+            self.data.hits += 1;
+        } else {
+            self.data.misses += 1;
+        }
+        self.dirty = true;
+        found
+    }
+
+    /// 写入一条译文，并在超出 `max_entries` 时淘汰最久未使用的条目。
+    ///
+    /// `backend_key` 用于缓存键 (见 `backend_key`)，`backend` 为显示给用户的后端名称。
+    pub fn insert(&mut self, text: &str, pair: &LanguagePair, backend_key: &str, backend: &str, translated: &str, max_entries: usize) {
+        if self.path.is_none() || max_entries == 0 {
+            return;
+        }
+        self.data.entries.insert(cache_key(text, pair, backend_key), CacheEntry {
+            translated: translated.to_string(),
+            backend: backend.to_string(),
+            last_used: now_secs(),
+        });
+
+        let overflow = self.data.entries.len().saturating_sub(max_entries);
+        if overflow > 0 {
+            let mut by_age: Vec<(u64, String)> = self.data.entries.iter()
+                .map(|(key, entry)| (entry.last_used, key.clone()))
+                .collect();
+            by_age.sort_unstable();
+            for (_, key) in by_age.into_iter().take(overflow) {
+                self.data.entries.remove(&key);
+            }
+            println!("[CACHE] 超出条目上限 {}，已淘汰 {} 条旧缓存", max_entries, overflow);
+        }
+        self.dirty = true;
+    }
+
+    /// 清空所有缓存条目与统计数据。
+    pub fn purge(&mut self) -> Result<(), AppError> {
+        self.data = CacheFile::default();
+        self.dirty = false;
+        if let Some(path) = &self.path {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.data.entries.len(),
+            hits: self.data.hits,
+            misses: self.data.misses,
+            file_size: self.path.as_deref().and_then(|p| fs::metadata(p).ok()).map_or(0, |m| m.len()),
+        }
+    }
+
+    /// 将尚未保存的修改写入缓存文件。
+    pub fn flush(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        let Some(path) = &self.path else { return };
+        match write_cache_file(path, &self.data) {
+            Ok(()) => self.dirty = false,
+            Err(e) => eprintln!("[CACHE] 保存翻译缓存失败: {}", e),
+        }
+    }
+}
+
+fn write_cache_file(path: &Path, data: &CacheFile) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string(data)?;
+    fs::write(path, content)
+}
+
+/// 规范化原文：合并每行内的连续空白并去掉首尾空白，使 OCR 结果中细微的空白差异不影响命中。
+///
+/// 换行会影响译文的排版，因此保留。
+fn normalize_text(text: &str) -> String {
+    text.trim()
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 缓存键中的后端部分。大模型与 LibreTranslate 的译文取决于接口地址 (及模型)，
+/// 切换模型或服务后不使用之前的译文。
+pub fn backend_key(settings: &AppSettings, backend: &str) -> String {
+    match backend {
+        "llm" => format!("llm|{}|{}", settings.llm.api_url.trim(), settings.llm.model.trim()),
+        "libretranslate" => format!("libretranslate|{}", settings.libretranslate.api_url.trim().trim_end_matches('/')),
+        other => other.to_string(),
+    }
+}
+
+fn cache_key(text: &str, pair: &LanguagePair, backend: &str) -> String {
+    format!("{}\u{1f}{}\u{1f}{}\u{1f}{}", backend, pair.source, pair.target, normalize_text(text))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("screentranslator-cache-test-{}-{}.json", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn lookups_are_written_only_on_flush() {
+        let path = temp_cache_path("flush");
+        let mut cache = TranslationCache::load(path.clone());
+        let pair = LanguagePair::new("en", "zh");
+
+        assert_eq!(cache.get("Hello", &pair, &["local".to_string()]), None);
+        cache.insert("Hello", &pair, "local", "local", "你好", 10);
+        assert!(!path.exists());
+
+        cache.flush();
+        let reloaded = TranslationCache::load(path.clone());
+        assert_eq!(reloaded.stats().entries, 1);
+        assert_eq!(reloaded.stats().misses, 1);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn llm_entries_are_keyed_by_model_and_url() {
+        let mut settings = AppSettings::default();
+        let mut cache = TranslationCache::load(temp_cache_path("llm-key"));
+        let pair = LanguagePair::new("en", "zh");
+
+        cache.insert("Hello", &pair, &backend_key(&settings, "llm"), "llm", "你好", 10);
+        assert_eq!(cache.get("Hello", &pair, &[backend_key(&settings, "llm")]), Some(("你好".to_string(), "llm".to_string())));
+
+        settings.llm.model = "another-model".to_string();
+        assert_eq!(cache.get("Hello", &pair, &[backend_key(&settings, "llm")]), None);
+        settings = AppSettings::default();
+        settings.llm.api_url = "http://192.168.1.10:8080/v1/chat/completions".to_string();
+        assert_eq!(cache.get("Hello", &pair, &[backend_key(&settings, "llm")]), None);
+    }
+
+    #[test]
+    fn whitespace_differences_still_hit() {
+        let mut cache = TranslationCache::load(temp_cache_path("whitespace"));
+        let pair = LanguagePair::new("en", "zh");
+        cache.insert("  Hello   world\nAgain ", &pair, "local", "local", "你好世界\n再次", 10);
+        assert!(cache.get("Hello world\nAgain", &pair, &["local".to_string()]).is_some());
+    }
+}
@@ -1,12 +1,20 @@
 // 文件: src-tauri\src\translator.rs
 
-use serde::{Deserialize};
-use tauri::AppHandle;
-use std::process::Command;
-// 移除 GBK 依赖，统一使用 UTF-8
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-#[cfg(windows)]
-use std::os::windows::process::CommandExt;
+use crate::error::AppError;
+use crate::i18n::{t, tf};
+use crate::lang_detect;
+use crate::manifest;
+use crate::settings::{AppSettings, AppState, LibreTranslateConfig, LlmTranslatorConfig};
+use crate::worker::{escape_non_ascii, run_with_timeout, WorkerSpec};
+use tokio::process::Command;
+// 移除 GBK 依赖，统一使用 UTF-8
 
 #[derive(Debug, Deserialize)]
 struct LocalTranslationResponse {
@@ -15,69 +23,164 @@ struct LocalTranslationResponse {
     error_message: Option<String>,
 }
 
+// 本地翻译引擎在引擎清单中的 ID，同时是常驻进程在进程池中的名称
+pub const TRANSLATOR_WORKER_NAME: &str = "translator";
+
+// 本地引擎未找到语言包元数据时默认支持的语言对
+const DEFAULT_LOCAL_PAIRS: &[(&str, &str)] = &[("en", "zh"), ("zh", "en")];
+
+// 大模型翻译可在以下语言之间任意互译 (代码, 提示词中使用的英文名称)
+const LLM_LANGUAGES: &[(&str, &str)] = &[
+    ("zh", "Simplified Chinese"),
+    ("en", "English"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("de", "German"),
+    ("fr", "French"),
+    ("es", "Spanish"),
+    ("it", "Italian"),
+    ("pt", "Portuguese"),
+    ("ru", "Russian"),
+];
+
+// HTTP 翻译请求的超时时间
+const HTTP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 翻译的源语言与目标语言。
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LanguagePair {
+    pub source: String,
+    pub target: String,
+}
+
+impl LanguagePair {
+    pub fn new(source: &str, target: &str) -> Self {
+        Self { source: source.to_string(), target: target.to_string() }
+    }
+}
+
+/// 流式翻译的回调，参数为目前为止得到的完整译文（而非增量）。
+pub type PartialCallback<'a> = &'a (dyn Fn(&str) + Send + Sync);
+
 #[async_trait::async_trait]
 pub trait Translator {
     async fn translate(
         &self,
         text: &str,
-        target_lang: &str,
-    ) -> Result<String, String>;
+        pair: &LanguagePair,
+    ) -> Result<String, AppError>;
+
+    /// 流式翻译，每得到新的输出就调用 `on_partial`。
+    ///
+    /// 默认实现适用于不支持流式输出的后端：翻译完成后一次性回调全文。
+    async fn translate_streaming(
+        &self,
+        text: &str,
+        pair: &LanguagePair,
+        on_partial: PartialCallback<'_>,
+    ) -> Result<String, AppError> {
+        let translated = self.translate(text, pair).await?;
+        on_partial(&translated);
+        Ok(translated)
+    }
+
+    /// 当前翻译后端实际支持的语言对。
+    async fn supported_pairs(&self) -> Result<Vec<LanguagePair>, AppError>;
+}
+
+/// 根据设置确定本次翻译的语言对。
+///
+/// 源语言设置为 "auto" 时检测文本语言；检测失败时退回到旧的规则（目标为英文则视为中文，否则视为英文）。
+pub fn resolve_language_pair(source_lang: &str, target_lang: &str, text: &str) -> LanguagePair {
+    if source_lang != "auto" {
+        return LanguagePair::new(source_lang, target_lang);
+    }
+    match lang_detect::detect_language(text) {
+        Some(detected) => {
+            println!("[TRANSLATOR] 自动检测到源语言: '{}'", detected);
+            LanguagePair::new(detected, target_lang)
+        }
+        None => {
+            let fallback = if target_lang == "en" { "zh" } else { "en" };
+            println!("[TRANSLATOR] 无法检测源语言，使用默认值: '{}'", fallback);
+            LanguagePair::new(fallback, target_lang)
+        }
+    }
+}
+
+/// 检查设置中的语言组合是否受支持。源语言为 "auto" 时，只要存在以目标语言为目标的语言对即可。
+pub fn is_pair_supported(pairs: &[LanguagePair], source_lang: &str, target_lang: &str) -> bool {
+    pairs.iter().any(|p| p.target == target_lang && (source_lang == "auto" || p.source == source_lang))
 }
 
 pub struct LocalTranslator {
     app_handle: AppHandle,
+    // 是否使用常驻进程模式，避免每次翻译都重新加载模型
+    persistent: bool,
+    // 单次翻译的超时时间，超时后终止引擎进程
+    timeout: Duration,
 }
 
 impl LocalTranslator {
-    pub fn new(app_handle: AppHandle) -> Self {
-        Self { app_handle }
+    pub fn new(app_handle: AppHandle, persistent: bool, timeout: Duration) -> Self {
+        Self { app_handle, persistent, timeout }
     }
-}
-
-#[async_trait::async_trait]
-impl Translator for LocalTranslator {
-    async fn translate(
-        &self,
-        text: &str,
-        target_lang: &str,
-    ) -> Result<String, String> {
-        // --- 修改核心逻辑：指向本地数据目录 ---
-        let local_data_dir = self.app_handle.path_resolver().app_local_data_dir()
-            .ok_or_else(|| "无法获取本地数据目录".to_string())?;
 
-        // 可执行文件名更改为 translate_engine.exe
-        let translator_exe_path = local_data_dir.join("translate_engine.exe");
+    fn exe_path(&self) -> Result<PathBuf, AppError> {
+        // 安装位置由引擎清单决定
+        let translator_exe_path = manifest::entry_path(&self.app_handle, TRANSLATOR_WORKER_NAME)?;
 
         println!("[TRANSLATOR] 检查翻译引擎: 路径='{:?}', 是否存在={}", translator_exe_path, translator_exe_path.exists());
 
         if !translator_exe_path.exists() {
-            return Err("找不到翻译引擎，请在设置页面下载安装。".to_string());
+            return Err(AppError::EngineMissing { engine: TRANSLATOR_WORKER_NAME.to_string() });
         }
+        Ok(translator_exe_path)
+    }
 
-        let source_lang = if target_lang == "en" { "zh" } else { "en" };
+    /// 通过常驻进程翻译：每行一个 `{"text","source","target"}` 请求，返回与单次模式相同的 JSON。
+    async fn translate_with_daemon(&self, exe_path: &Path, text: &str, pair: &LanguagePair) -> Result<String, AppError> {
+        let engine = manifest::engine_spec(&self.app_handle, TRANSLATOR_WORKER_NAME)?;
+        let spec = WorkerSpec {
+            tag: TRANSLATOR_WORKER_NAME,
+            exe_path,
+            args: &engine.args.iter().map(String::as_str).collect::<Vec<_>>(),
+            ready_marker: engine.ready_marker.as_deref(),
+            timeout: self.timeout,
+        };
+        let request = escape_non_ascii(&serde_json::json!({
+            "text": text,
+            "source": pair.source,
+            "target": pair.target,
+        }).to_string());
 
-        println!("[TRANSLATOR] 翻译请求: 源语言='{}', 目标语言='{}', 文本='{}...'", source_lang, target_lang, text.chars().take(50).collect::<String>());
+        let worker = self.app_handle.state::<AppState>().engine_workers.get(TRANSLATOR_WORKER_NAME);
+        let stdout = worker.request(&spec, &request).await?;
+        println!("[TRANSLATOR] 常驻进程输出: {}", stdout);
+        parse_local_response(&stdout)
+    }
 
+    /// 单次模式：每次翻译启动一个新进程，通过命令行参数传入原文。
+    async fn translate_once(&self, exe_path: &Path, text: &str, pair: &LanguagePair) -> Result<String, AppError> {
         // 确保工作目录为可执行文件所在目录，以便加载依赖
-        let working_dir = translator_exe_path.parent().unwrap();
+        let working_dir = exe_path.parent()
+            .ok_or_else(|| AppError::ManifestInvalid { detail: exe_path.to_string_lossy().into_owned() })?;
 
-        let mut command = Command::new(&translator_exe_path);
+        let mut command = Command::new(exe_path);
         command.current_dir(working_dir)
             .env("PYTHONIOENCODING", "utf-8")
             .env("PYTHONUTF8", "1")
             .env("PYTHONLEGACYWINDOWSSTDIO", "0")
             .args(&[
                 "--text", text,
-                "--source", source_lang,
-                "--target", target_lang,
+                "--source", &pair.source,
+                "--target", &pair.target,
             ]);
 
         #[cfg(windows)]
         command.creation_flags(0x08000000); // CREATE_NO_WINDOW
 
-        let output = command
-            .output()
-            .map_err(|e| format!("执行翻译进程失败: {}", e))?;
+        let output = run_with_timeout(command, TRANSLATOR_WORKER_NAME, self.timeout).await?;
 
         println!("[TRANSLATOR] 进程执行完毕. Status: {:?}", output.status);
 
@@ -85,24 +188,561 @@ impl Translator for LocalTranslator {
             // 先尝试 UTF-8 解码 stderr
             let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
             eprintln!("[TRANSLATOR] 进程执行出错, Status: {:?}, Stderr: {}", output.status, stderr);
-            return Err(format!("翻译进程执行出错: {}", stderr));
+            return Err(AppError::EngineCrashed { engine: TRANSLATOR_WORKER_NAME.to_string(), detail: stderr });
         }
 
         // --- 核心修复：统一使用 UTF-8 解码 ---
         let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
         println!("[TRANSLATOR] 原始输出 (UTF-8 decoded stdout): {}", stdout);
+        parse_local_response(&stdout)
+    }
+}
+
+fn parse_local_response(stdout: &str) -> Result<String, AppError> {
+    let response: LocalTranslationResponse = serde_json::from_str(stdout)
+        .map_err(|e| AppError::ParseFailure { source: TRANSLATOR_WORKER_NAME.to_string(), detail: tf("detail.raw_output", &[("error", &e), ("output", &stdout)]) })?;
+    println!("[TRANSLATOR] 解析到的响应: {:?}", response);
+
+    match response.code {
+        200 => response.translated_text.ok_or_else(|| AppError::ParseFailure {
+            source: TRANSLATOR_WORKER_NAME.to_string(),
+            detail: t("detail.empty_translation"),
+        }),
+        _ => Err(AppError::EngineError {
+            engine: TRANSLATOR_WORKER_NAME.to_string(),
+            detail: response.error_message.unwrap_or_else(|| t("detail.unknown_error")),
+        }),
+    }
+}
+
+#[async_trait::async_trait]
+impl Translator for LocalTranslator {
+    async fn translate(
+        &self,
+        text: &str,
+        pair: &LanguagePair,
+    ) -> Result<String, AppError> {
+        let translator_exe_path = self.exe_path()?;
+
+        println!("[TRANSLATOR] 翻译请求: 源语言='{}', 目标语言='{}', 文本='{}...'", pair.source, pair.target, text.chars().take(50).collect::<String>());
+
+        let state = self.app_handle.state::<AppState>();
+        let daemon_supported = !state.translator_daemon_unsupported.lock().unwrap().contains(&translator_exe_path);
+        if !self.persistent || !daemon_supported {
+            return self.translate_once(&translator_exe_path, text, pair).await;
+        }
+
+        match self.translate_with_daemon(&translator_exe_path, text, pair).await {
+            // 进程无法启动或输出不符合协议时，可能是不支持常驻模式的旧版本引擎，退回单次模式。
+            // 超时说明引擎已在处理请求，重试只会让等待时间加倍
+            Err(e @ (AppError::EngineCrashed { .. } | AppError::ParseFailure { .. })) => {
+                println!("[TRANSLATOR] 常驻进程翻译失败: {}，改用单次模式。", e);
+                let translated = self.translate_once(&translator_exe_path, text, pair).await?;
+                // 单次模式可以正常翻译，说明该引擎不支持常驻模式，之后直接使用单次模式
+                println!("[TRANSLATOR] 引擎不支持常驻模式，之后直接使用单次模式: {:?}", translator_exe_path);
+                state.translator_daemon_unsupported.lock().unwrap().insert(translator_exe_path);
+                Ok(translated)
+            }
+            result => result,
+        }
+    }
+
+    /// 读取已安装语言包 (packages/*/metadata.json) 中的 from_code / to_code。
+    async fn supported_pairs(&self) -> Result<Vec<LanguagePair>, AppError> {
+        // 语言包位于引擎可执行文件所在目录下
+        let packages_dir = manifest::entry_path(&self.app_handle, TRANSLATOR_WORKER_NAME)?
+            .with_file_name("packages");
+
+        let mut pairs = Vec::new();
+        if let Ok(entries) = fs::read_dir(&packages_dir) {
+            for entry in entries.flatten() {
+                let Ok(content) = fs::read_to_string(entry.path().join("metadata.json")) else { continue };
+                let Ok(metadata) = serde_json::from_str::<serde_json::Value>(&content) else { continue };
+                if let (Some(from), Some(to)) = (metadata["from_code"].as_str(), metadata["to_code"].as_str()) {
+                    pairs.push(LanguagePair::new(from, to));
+                }
+            }
+        }
+
+        if pairs.is_empty() {
+            pairs = DEFAULT_LOCAL_PAIRS.iter().map(|(s, t)| LanguagePair::new(s, t)).collect();
+        }
+        println!("[TRANSLATOR] 本地引擎支持的语言对: {:?}", pairs);
+        Ok(pairs)
+    }
+}
+
+/// OpenAI 兼容接口 (Ollama、llama.cpp server 等) 的大模型翻译。
+pub struct LlmTranslator {
+    config: LlmTranslatorConfig,
+    client: reqwest::Client,
+}
+
+impl LlmTranslator {
+    pub fn new(config: LlmTranslatorConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { config, client }
+    }
+
+    fn build_request(&self, text: &str, pair: &LanguagePair, stream: bool) -> reqwest::RequestBuilder {
+        let body = serde_json::json!({
+            "model": self.config.model,
+            "temperature": self.config.temperature,
+            "stream": stream,
+            "messages": [
+                { "role": "user", "content": self.render_prompt(text, pair) }
+            ],
+        });
+
+        let mut request = self.client.post(&self.config.api_url).json(&body);
+        if !self.config.api_key.is_empty() {
+            request = request.bearer_auth(&self.config.api_key);
+        }
+        request
+    }
+
+    /// 用语言名称和原文填充提示词模板中的 {source_lang}、{target_lang}、{text} 占位符。
+    fn render_prompt(&self, text: &str, pair: &LanguagePair) -> String {
+        self.config.prompt_template
+            .replace("{source_lang}", language_name(&pair.source))
+            .replace("{target_lang}", language_name(&pair.target))
+            .replace("{text}", text)
+    }
+}
+
+fn llm_network_error(detail: String) -> AppError {
+    AppError::Network { service: "llm".to_string(), detail }
+}
+
+fn llm_parse_error(detail: String) -> AppError {
+    AppError::ParseFailure { source: "llm".to_string(), detail }
+}
+
+/// 取出 SSE 中一行 `data: {...}` 的 `delta.content`。注释行、`[DONE]` 以及不含文本的行返回 None。
+fn parse_sse_delta(line: &str) -> Option<String> {
+    let data = line.trim().strip_prefix("data:")?;
+    let value: serde_json::Value = serde_json::from_str(data.trim()).ok()?;
+    value["choices"][0]["delta"]["content"].as_str().map(str::to_string)
+}
+
+fn language_name(code: &str) -> &str {
+    LLM_LANGUAGES.iter().find(|(c, _)| *c == code).map_or(code, |(_, name)| *name)
+}
+
+#[async_trait::async_trait]
+impl Translator for LlmTranslator {
+    async fn translate(
+        &self,
+        text: &str,
+        pair: &LanguagePair,
+    ) -> Result<String, AppError> {
+        println!("[TRANSLATOR:LLM] 翻译请求: 接口='{}', 模型='{}', {} -> {}", self.config.api_url, self.config.model, pair.source, pair.target);
+
+        let res = self.build_request(text, pair, false).send().await.map_err(|e| llm_network_error(e.to_string()))?;
+        let status = res.status();
+        let value: serde_json::Value = res.json().await.map_err(|e| llm_parse_error(e.to_string()))?;
+        if !status.is_success() {
+            let message = value["error"]["message"].as_str().map_or_else(|| t("detail.unknown_error"), str::to_string);
+            return Err(llm_network_error(tf("detail.api_error", &[("status", &status), ("message", &message)])));
+        }
+
+        value["choices"][0]["message"]["content"].as_str()
+            .map(|content| content.trim().to_string())
+            .filter(|content| !content.is_empty())
+            .ok_or_else(|| llm_parse_error(t("detail.empty_translation")))
+    }
+
+    /// 以 SSE (`data: {...}` 行) 方式接收输出，每收到一段 `delta.content` 就回调一次。
+    async fn translate_streaming(
+        &self,
+        text: &str,
+        pair: &LanguagePair,
+        on_partial: PartialCallback<'_>,
+    ) -> Result<String, AppError> {
+        if !self.config.stream {
+            let translated = self.translate(text, pair).await?;
+            on_partial(&translated);
+            return Ok(translated);
+        }
+        println!("[TRANSLATOR:LLM] 流式翻译请求: 接口='{}', 模型='{}', {} -> {}", self.config.api_url, self.config.model, pair.source, pair.target);
+
+        let res = self.build_request(text, pair, true).send().await.map_err(|e| llm_network_error(e.to_string()))?;
+        let status = res.status();
+        if !status.is_success() {
+            let value: serde_json::Value = res.json().await.unwrap_or_default();
+            let message = value["error"]["message"].as_str().map_or_else(|| t("detail.unknown_error"), str::to_string);
+            return Err(llm_network_error(tf("detail.api_error", &[("status", &status), ("message", &message)])));
+        }
+
+        let mut content = String::new();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut stream = res.bytes_stream();
+        while let Some(item) = stream.next().await {
+            let bytes = item.map_err(|e| llm_network_error(tf("detail.stream_read_failed", &[("error", &e)])))?;
+            buffer.extend_from_slice(&bytes);
+            // 按行解析，不完整的行留在缓冲区中等待后续数据
+            while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=pos).collect();
+                if let Some(delta) = parse_sse_delta(&String::from_utf8_lossy(&line)) {
+                    content.push_str(&delta);
+                    on_partial(content.trim_start());
+                }
+            }
+        }
+
+        let content = content.trim().to_string();
+        if content.is_empty() {
+            return Err(llm_parse_error(t("detail.empty_translation")));
+        }
+        Ok(content)
+    }
+
+    async fn supported_pairs(&self) -> Result<Vec<LanguagePair>, AppError> {
+        Ok(LLM_LANGUAGES.iter()
+            .flat_map(|(source, _)| LLM_LANGUAGES.iter()
+                .filter(move |(target, _)| target != source)
+                .map(move |(target, _)| LanguagePair::new(source, target)))
+            .collect())
+    }
+}
+
+/// LibreTranslate 兼容服务 (`/translate`、`/languages` 接口) 的翻译。
+pub struct LibreTranslator {
+    config: LibreTranslateConfig,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct LibreLanguage {
+    code: String,
+    // 旧版本服务不返回该字段，此时视为可与其他所有语言互译
+    #[serde(default)]
+    targets: Option<Vec<String>>,
+}
+
+impl LibreTranslator {
+    pub fn new(config: LibreTranslateConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { config, client }
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{}", self.config.api_url.trim_end_matches('/'), path)
+    }
+}
+
+fn libre_network_error(detail: String) -> AppError {
+    AppError::Network { service: "libretranslate".to_string(), detail }
+}
+
+fn libre_parse_error(detail: String) -> AppError {
+    AppError::ParseFailure { source: "libretranslate".to_string(), detail }
+}
+
+/// 新版 LibreTranslate 使用 "zh-Hans" 表示简体中文，统一为本程序使用的 "zh"。
+fn normalize_libre_code(code: &str) -> &str {
+    if code == "zh-Hans" { "zh" } else { code }
+}
+
+#[async_trait::async_trait]
+impl Translator for LibreTranslator {
+    async fn translate(
+        &self,
+        text: &str,
+        pair: &LanguagePair,
+    ) -> Result<String, AppError> {
+        println!("[TRANSLATOR:LIBRE] 翻译请求: 服务='{}', {} -> {}", self.config.api_url, pair.source, pair.target);
+
+        let mut body = serde_json::json!({
+            "q": text,
+            "source": pair.source,
+            "target": pair.target,
+            "format": "text",
+        });
+        if !self.config.api_key.is_empty() {
+            body["api_key"] = serde_json::Value::String(self.config.api_key.clone());
+        }
+
+        let res = self.client.post(self.endpoint("translate")).json(&body).send().await
+            .map_err(|e| libre_network_error(e.to_string()))?;
+        let status = res.status();
+        let value: serde_json::Value = res.json().await.map_err(|e| libre_parse_error(e.to_string()))?;
+        if !status.is_success() {
+            let message = value["error"].as_str().map_or_else(|| t("detail.unknown_error"), str::to_string);
+            return Err(libre_network_error(tf("detail.api_error", &[("status", &status), ("message", &message)])));
+        }
+
+        value["translatedText"].as_str()
+            .map(|translated| translated.to_string())
+            .ok_or_else(|| libre_parse_error(t("detail.empty_translation")))
+    }
+
+    async fn supported_pairs(&self) -> Result<Vec<LanguagePair>, AppError> {
+        let mut request = self.client.get(self.endpoint("languages"));
+        if !self.config.api_key.is_empty() {
+            request = request.query(&[("api_key", &self.config.api_key)]);
+        }
+        let languages: Vec<LibreLanguage> = request.send().await
+            .map_err(|e| libre_network_error(tf("detail.languages_fetch_failed", &[("error", &e)])))?
+            .json().await
+            .map_err(|e| libre_parse_error(tf("detail.languages_invalid", &[("error", &e)])))?;
+
+        let mut pairs = Vec::new();
+        for language in &languages {
+            let source = normalize_libre_code(&language.code);
+            let targets: Vec<&str> = match &language.targets {
+                Some(targets) => targets.iter().map(|t| normalize_libre_code(t)).collect(),
+                None => languages.iter().map(|l| normalize_libre_code(&l.code)).collect(),
+            };
+            for target in targets.into_iter().filter(|t| *t != source) {
+                pairs.push(LanguagePair::new(source, target));
+            }
+        }
+        println!("[TRANSLATOR:LIBRE] 服务支持 {} 个语言对", pairs.len());
+        Ok(pairs)
+    }
+}
+
+/// 按顺序尝试多个翻译后端的组合翻译器。
+///
+/// 前一个后端失败时自动尝试下一个，全部失败时汇总每个后端的错误信息。
+pub struct FallbackTranslator {
+    backends: Vec<(String, Box<dyn Translator + Send + Sync>)>,
+}
+
+impl FallbackTranslator {
+    pub fn new(backends: Vec<(String, Box<dyn Translator + Send + Sync>)>) -> Self {
+        Self { backends }
+    }
 
-        let response: LocalTranslationResponse = serde_json::from_str(&stdout)
-            .map_err(|e| format!("解析翻译结果JSON失败: {}. 原始输出: {}", e, stdout))?;
-        println!("[TRANSLATOR] 解析到的响应: {:?}", response);
+    /// 按尝试顺序排列的后端名称。
+    pub fn backend_names(&self) -> Vec<String> {
+        self.backends.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// 依次尝试各后端，返回 (译文, 翻译成功的后端名称)。
+    ///
+    /// 长文本分段并行翻译时，各片段可能由不同的后端完成，因此通过返回值而非内部状态报告后端。
+    /// `on_partial` 收到的是完整的阶段性译文，切换到备用后端后会被新后端的输出覆盖。
+    pub async fn translate_with_backend(&self, text: &str, pair: &LanguagePair, on_partial: PartialCallback<'_>) -> Result<(String, String), AppError> {
+        let mut errors = Vec::new();
+        for (name, backend) in &self.backends {
+            match backend.translate_streaming(text, pair, on_partial).await {
+                Ok(translated) => {
+                    if !errors.is_empty() {
+                        println!("[TRANSLATOR] 已回退到备用翻译后端 '{}'", name);
+                    }
+                    return Ok((translated, name.clone()));
+                }
+                Err(e) => {
+                    println!("[TRANSLATOR] 翻译后端 '{}' 失败: {}", name, e);
+                    errors.push((name.clone(), e));
+                }
+            }
+        }
+        Err(combine_errors(errors))
+    }
+}
+
+#[async_trait::async_trait]
+impl Translator for FallbackTranslator {
+    async fn translate(
+        &self,
+        text: &str,
+        pair: &LanguagePair,
+    ) -> Result<String, AppError> {
+        self.translate_with_backend(text, pair, &|_| {}).await.map(|(translated, _)| translated)
+    }
 
-        match response.code {
-            200 => response.translated_text.ok_or_else(|| "翻译成功但未返回文本".to_string()),
-            _ => Err(response.error_message.unwrap_or_else(|| "翻译器返回未知错误".to_string())),
+    /// 所有后端支持的语言对的并集；只有全部后端都获取失败时才返回错误。
+    async fn supported_pairs(&self) -> Result<Vec<LanguagePair>, AppError> {
+        let mut pairs: Vec<LanguagePair> = Vec::new();
+        let mut errors = Vec::new();
+        for (name, backend) in &self.backends {
+            match backend.supported_pairs().await {
+                Ok(backend_pairs) => {
+                    for pair in backend_pairs {
+                        if !pairs.contains(&pair) {
+                            pairs.push(pair);
+                        }
+                    }
+                }
+                Err(e) => errors.push((name.clone(), e)),
+            }
+        }
+        if pairs.is_empty() && !errors.is_empty() {
+            return Err(combine_errors(errors));
         }
+        Ok(pairs)
     }
 }
 
-pub fn get_translator(app: &AppHandle) -> Box<dyn Translator + Send + Sync> {
-    Box::new(LocalTranslator::new(app.clone()))
-}
\ No newline at end of file
+/// 只配置了一个后端时直接返回其错误，否则汇总每个后端的错误。
+fn combine_errors(mut errors: Vec<(String, AppError)>) -> AppError {
+    if errors.len() == 1 {
+        errors.remove(0).1
+    } else {
+        AppError::AllBackendsFailed { errors }
+    }
+}
+
+fn create_backend(app: &AppHandle, settings: &AppSettings, name: &str) -> Box<dyn Translator + Send + Sync> {
+    match name {
+        "llm" => Box::new(LlmTranslator::new(settings.llm.clone())),
+        "libretranslate" => Box::new(LibreTranslator::new(settings.libretranslate.clone())),
+        _ => Box::new(LocalTranslator::new(app.clone(), settings.translator_daemon, settings.engine_timeout())),
+    }
+}
+
+/// 根据设置中的 `translator_backend` 与 `translator_fallbacks` 构建翻译后端链。
+pub fn get_translator(app: &AppHandle, settings: &AppSettings) -> FallbackTranslator {
+    let mut names: Vec<&str> = vec![settings.translator_backend.as_str()];
+    for name in &settings.translator_fallbacks {
+        if !names.contains(&name.as_str()) {
+            names.push(name);
+        }
+    }
+    FallbackTranslator::new(names.into_iter()
+        .map(|name| (name.to_string(), create_backend(app, settings, name)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn llm_config(api_url: String, stream: bool) -> LlmTranslatorConfig {
+        LlmTranslatorConfig { api_url, stream, ..LlmTranslatorConfig::default() }
+    }
+
+    /// 在本地启动只处理一个请求的 HTTP 服务，依次写出 `chunks` 作为响应。返回服务地址。
+    async fn serve_once(chunks: Vec<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            // 读完请求头与请求体后再响应
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some(header_end) = text.find("\r\n\r\n") {
+                    let content_length = text[..header_end].lines()
+                        .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                        .unwrap_or(0);
+                    if request.len() >= header_end + 4 + content_length {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+            for chunk in chunks {
+                socket.write_all(chunk.as_bytes()).await.unwrap();
+                socket.flush().await.unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        });
+        format!("http://{}/v1/chat/completions", addr)
+    }
+
+    #[test]
+    fn render_prompt_fills_placeholders() {
+        let mut config = llm_config(String::new(), false);
+        config.prompt_template = "{source_lang} -> {target_lang}: {text} ({unknown})".to_string();
+        let translator = LlmTranslator::new(config);
+        let prompt = translator.render_prompt("Hello", &LanguagePair::new("en", "zh"));
+        assert_eq!(prompt, "English -> Simplified Chinese: Hello ({unknown})");
+    }
+
+    #[test]
+    fn render_prompt_keeps_unknown_language_codes() {
+        let translator = LlmTranslator::new(llm_config(String::new(), false));
+        let prompt = translator.render_prompt("Hej", &LanguagePair::new("sv", "en"));
+        assert!(prompt.starts_with("Translate the following sv text into English."));
+        assert!(prompt.ends_with("\n\nHej"));
+    }
+
+    #[test]
+    fn parse_sse_delta_reads_content() {
+        assert_eq!(parse_sse_delta(r#"data: {"choices":[{"delta":{"content":"你好"}}]}"#).as_deref(), Some("你好"));
+        assert_eq!(parse_sse_delta(r#"data:{"choices":[{"delta":{"content":" world"}}]}"#).as_deref(), Some(" world"));
+    }
+
+    #[test]
+    fn parse_sse_delta_skips_other_lines() {
+        assert_eq!(parse_sse_delta("data: [DONE]"), None);
+        assert_eq!(parse_sse_delta(": keep-alive"), None);
+        assert_eq!(parse_sse_delta(""), None);
+        assert_eq!(parse_sse_delta(r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#), None);
+    }
+
+    #[tokio::test]
+    async fn translate_streaming_joins_deltas_split_across_packets() {
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"你\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"好\"}}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        // 在一行 JSON 中间断开，检验不完整的行会等待后续数据
+        let split = body.find("好").unwrap();
+        let url = serve_once(vec![
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n".to_string(),
+            body[..split].to_string(),
+            body[split..].to_string(),
+        ]).await;
+
+        let translator = LlmTranslator::new(llm_config(url, true));
+        let partials = Mutex::new(Vec::new());
+        let on_partial = |partial: &str| partials.lock().unwrap().push(partial.to_string());
+        let translated = translator.translate_streaming("Hello", &LanguagePair::new("en", "zh"), &on_partial).await.unwrap();
+
+        assert_eq!(translated, "你好");
+        assert_eq!(*partials.lock().unwrap(), vec!["你".to_string(), "你好".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn translate_streaming_reports_api_errors() {
+        let body = r#"{"error":{"message":"model not found"}}"#;
+        let url = serve_once(vec![format!(
+            "HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(), body,
+        )]).await;
+
+        let translator = LlmTranslator::new(llm_config(url, true));
+        let err = translator.translate_streaming("Hello", &LanguagePair::new("en", "zh"), &|_| {}).await.unwrap_err();
+        match err {
+            AppError::Network { service, detail } => {
+                assert_eq!(service, "llm");
+                assert!(detail.contains("model not found"), "{}", detail);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn translate_reads_non_streaming_response() {
+        let body = r#"{"choices":[{"message":{"role":"assistant","content":"  你好  "}}]}"#;
+        let url = serve_once(vec![format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(), body,
+        )]).await;
+
+        let translator = LlmTranslator::new(llm_config(url, false));
+        let translated = translator.translate("Hello", &LanguagePair::new("en", "zh")).await.unwrap();
+        assert_eq!(translated, "你好");
+    }
+}
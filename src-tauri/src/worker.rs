@@ -0,0 +1,355 @@
+// --- 文件: src-tauri/src/worker.rs ---
+//
+// 常驻引擎进程管理。
+// 引擎进程启动后通过 stdin/stdout 以"一行一个 JSON"的方式收发请求，
+// 避免每次识别或翻译都重新加载模型。进程崩溃时自动重启，空闲超时或程序退出时关闭。
+// 程序退出时不等待正在处理的请求，直接终止进程。
+// 所有读写均为异步 IO (tokio::process)，等待引擎时不占用 tokio 工作线程。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+use crate::error::AppError;
+use crate::i18n::{t, tf};
+
+/// 启动一个引擎进程所需的信息。
+pub struct WorkerSpec<'a> {
+    /// 引擎 ID，用于日志前缀与错误信息，例如 "rapidocr"。
+    pub tag: &'a str,
+    pub exe_path: &'a Path,
+    pub args: &'a [&'a str],
+    /// 引擎初始化完成时输出的标志行，为 `None` 时启动后立即可用。
+    pub ready_marker: Option<&'a str>,
+    /// 单次请求（以及初始化）的最长等待时间，超时后终止进程。
+    pub timeout: Duration,
+}
+
+struct WorkerProcess {
+    exe_path: PathBuf,
+    // 与 `EngineWorker::running` 共享，终止进程时无需等待请求锁
+    child: Arc<Mutex<Child>>,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+/// 一次读写的失败原因。超时不应重启进程重试。
+enum ExchangeError {
+    Failed(String),
+    TimedOut,
+}
+
+/// 请求进行期间持有，请求所在的任务被取消（future 被丢弃）时终止引擎进程。
+///
+/// 此时引擎仍在处理被放弃的请求，其输出会与下一次请求错位，必须重启进程。
+struct KillOnCancel<'a> {
+    child: &'a Mutex<Child>,
+    armed: bool,
+}
+
+impl Drop for KillOnCancel<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            let mut child = self.child.lock().unwrap();
+            println!("[WORKER] 请求被中断，终止引擎进程, PID={}", child.id().unwrap_or_default());
+            let _ = child.start_kill();
+        }
+    }
+}
+
+/// 单个常驻引擎进程的管理器。
+///
+/// 请求在内部互斥锁中串行执行，引擎一次只处理一个请求。
+#[derive(Default)]
+pub struct EngineWorker {
+    process: tokio::sync::Mutex<Option<WorkerProcess>>,
+    last_used: Mutex<Option<Instant>>,
+    // 最近启动的进程，请求进行中（持有请求锁）时也能通过它终止进程
+    running: Mutex<Option<Arc<Mutex<Child>>>>,
+}
+
+impl EngineWorker {
+    /// 向引擎发送一行请求并返回其输出的第一行 JSON。
+    ///
+    /// 进程尚未启动、已退出或通信失败时会重新启动进程并重试一次；
+    /// 超时时终止进程并直接返回错误。
+    pub async fn request(&self, spec: &WorkerSpec<'_>, request_line: &str) -> Result<String, AppError> {
+        let mut guard = self.process.lock().await;
+        *self.last_used.lock().unwrap() = Some(Instant::now());
+
+        // 引擎路径发生变化（例如重新安装到其他目录）时，关闭旧进程
+        if guard.as_ref().is_some_and(|p| p.exe_path != spec.exe_path) {
+            if let Some(old) = guard.take() {
+                kill_process(spec.tag, old);
+            }
+        }
+
+        let mut last_error = String::new();
+        for attempt in 0..2 {
+            // 健康检查：进程已意外退出时先丢弃，再重新启动
+            if guard.as_mut().is_some_and(|p| has_exited(spec.tag, p)) {
+                *guard = None;
+            }
+            if guard.is_none() {
+                let process = spawn_process(spec).await?;
+                *self.running.lock().unwrap() = Some(process.child.clone());
+                *guard = Some(process);
+            }
+            let process = guard.as_mut().unwrap();
+
+            match exchange(process, spec, request_line).await {
+                Ok(line) => {
+                    *self.last_used.lock().unwrap() = Some(Instant::now());
+                    return Ok(line);
+                }
+                Err(ExchangeError::TimedOut) => {
+                    if let Some(process) = guard.take() {
+                        kill_process(spec.tag, process);
+                    }
+                    return Err(AppError::EngineTimeout { engine: spec.tag.to_string(), secs: spec.timeout.as_secs() });
+                }
+                Err(ExchangeError::Failed(e)) => {
+                    println!("[WORKER:{}] 第 {} 次请求失败: {}，正在重启引擎进程...", spec.tag, attempt + 1, e);
+                    if let Some(dead) = guard.take() {
+                        kill_process(spec.tag, dead);
+                    }
+                    last_error = e;
+                }
+            }
+        }
+        Err(AppError::EngineCrashed { engine: spec.tag.to_string(), detail: tf("detail.worker_io_failed", &[("error", &last_error)]) })
+    }
+
+    /// 若引擎空闲时间超过 `idle_timeout`，则关闭进程以释放内存。
+    pub fn shutdown_if_idle(&self, idle_timeout: Duration) {
+        // 正在处理请求时拿不到锁，直接跳过本轮检查
+        let Ok(mut guard) = self.process.try_lock() else { return };
+        let idle = self.last_used.lock().unwrap().is_none_or(|t| t.elapsed() >= idle_timeout);
+        if idle {
+            if let Some(process) = guard.take() {
+                println!("[WORKER] 引擎空闲超过 {} 秒，正在关闭...", idle_timeout.as_secs());
+                kill_process("IDLE", process);
+            }
+        }
+    }
+
+    /// 检查进程是否仍在运行，已崩溃退出的进程会被清理，下次请求时重新启动。
+    pub fn check_health(&self) {
+        let Ok(mut guard) = self.process.try_lock() else { return };
+        if guard.as_mut().is_some_and(|p| has_exited("HEALTH", p)) {
+            *guard = None;
+        }
+    }
+
+    /// 关闭引擎进程（重新安装引擎前调用），会等待正在处理的请求结束。
+    pub async fn shutdown(&self) {
+        if let Some(process) = self.process.lock().await.take() {
+            kill_process("SHUTDOWN", process);
+        }
+    }
+
+    /// 立即终止引擎进程，不等待正在处理的请求（程序退出时调用）。
+    pub fn kill(&self) {
+        if let Some(child) = self.running.lock().unwrap().take() {
+            let mut child = child.lock().unwrap();
+            if let Some(pid) = child.id() {
+                println!("[WORKER:EXIT] 终止引擎进程, PID={}", pid);
+                let _ = child.start_kill();
+            }
+        }
+    }
+}
+
+/// 按名称管理多个常驻引擎进程（每个引擎各自一个进程）。
+#[derive(Default)]
+pub struct WorkerPool {
+    workers: Mutex<HashMap<String, Arc<EngineWorker>>>,
+}
+
+impl WorkerPool {
+    /// 获取指定名称的引擎管理器，不存在时创建（进程在首次请求时才启动）。
+    pub fn get(&self, name: &str) -> Arc<EngineWorker> {
+        self.workers.lock().unwrap()
+            .entry(name.to_string())
+            .or_default()
+            .clone()
+    }
+
+    pub async fn shutdown(&self, name: &str) {
+        let worker = self.workers.lock().unwrap().get(name).cloned();
+        if let Some(worker) = worker {
+            worker.shutdown().await;
+        }
+    }
+
+    /// 立即终止所有引擎进程，不等待正在处理的请求。
+    pub fn kill_all(&self) {
+        let workers: Vec<_> = self.workers.lock().unwrap().values().cloned().collect();
+        for worker in workers {
+            worker.kill();
+        }
+    }
+
+    pub fn check_health(&self) {
+        let workers: Vec<_> = self.workers.lock().unwrap().values().cloned().collect();
+        for worker in workers {
+            worker.check_health();
+        }
+    }
+
+    pub fn shutdown_idle(&self, idle_timeout: Duration) {
+        let workers: Vec<_> = self.workers.lock().unwrap().values().cloned().collect();
+        for worker in workers {
+            worker.shutdown_if_idle(idle_timeout);
+        }
+    }
+}
+
+async fn spawn_process(spec: &WorkerSpec<'_>) -> Result<WorkerProcess, AppError> {
+    println!("[WORKER:{}] 启动常驻引擎进程: {:?} {:?}", spec.tag, spec.exe_path, spec.args);
+    let crashed = |detail: String| AppError::EngineCrashed { engine: spec.tag.to_string(), detail };
+    let working_dir = spec.exe_path.parent().ok_or_else(|| crashed(t("detail.engine_dir_missing")))?;
+
+    let mut command = Command::new(spec.exe_path);
+    command.args(spec.args)
+        .current_dir(working_dir)
+        .env("PYTHONIOENCODING", "utf-8")
+        .env("PYTHONUTF8", "1")
+        .env("PYTHONLEGACYWINDOWSSTDIO", "0")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        // 初始化期间任务被取消时，随 Child 一起终止进程
+        .kill_on_drop(true);
+    #[cfg(windows)]
+    command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let mut child = command.spawn().map_err(|e| crashed(tf("detail.spawn_failed", &[("error", &e)])))?;
+    let stdin = child.stdin.take().ok_or_else(|| crashed(t("detail.stdin_unavailable")))?;
+    let stdout = BufReader::new(child.stdout.take().ok_or_else(|| crashed(t("detail.stdout_unavailable")))?);
+    let pid = child.id().unwrap_or_default();
+    let mut process = WorkerProcess { exe_path: spec.exe_path.to_path_buf(), child: Arc::new(Mutex::new(child)), stdin, stdout };
+
+    if let Some(marker) = spec.ready_marker {
+        let ready = tokio::time::timeout(spec.timeout, async {
+            loop {
+                let line = read_line(&mut process.stdout).await?;
+                println!("[WORKER:{}] 初始化输出: {}", spec.tag, line);
+                if line.contains(marker) {
+                    return Ok::<(), String>(());
+                }
+            }
+        }).await;
+        let error = match ready {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(crashed(tf("detail.init_failed", &[("error", &e)]))),
+            Err(_) => Some(AppError::EngineTimeout { engine: spec.tag.to_string(), secs: spec.timeout.as_secs() }),
+        };
+        if let Some(e) = error {
+            kill_process(spec.tag, process);
+            return Err(e);
+        }
+    }
+    println!("[WORKER:{}] 引擎进程已就绪, PID={}", spec.tag, pid);
+    Ok(process)
+}
+
+/// 写入一行请求，并读取直到遇到以 '{' 开头的响应行（跳过引擎输出的日志行）。
+async fn exchange(process: &mut WorkerProcess, spec: &WorkerSpec<'_>, request_line: &str) -> Result<String, ExchangeError> {
+    let WorkerProcess { child, stdin, stdout, .. } = process;
+    let mut kill_on_cancel = KillOnCancel { child: child.as_ref(), armed: true };
+
+    let result = tokio::time::timeout(spec.timeout, async {
+        stdin.write_all(request_line.as_bytes()).await
+            .map_err(|e| tf("detail.write_failed", &[("error", &e)]))?;
+        stdin.write_all(b"\n").await
+            .map_err(|e| tf("detail.write_failed", &[("error", &e)]))?;
+        stdin.flush().await
+            .map_err(|e| tf("detail.write_failed", &[("error", &e)]))?;
+
+        loop {
+            let line = read_line(stdout).await?;
+            if line.starts_with('{') {
+                return Ok(line);
+            }
+            println!("[WORKER] 跳过非 JSON 输出: {}", line);
+        }
+    }).await;
+    kill_on_cancel.armed = false;
+
+    match result {
+        Ok(response) => response.map_err(ExchangeError::Failed),
+        Err(_) => {
+            println!("[WORKER:{}] 引擎超过 {} 秒未响应，终止进程", spec.tag, spec.timeout.as_secs());
+            Err(ExchangeError::TimedOut)
+        }
+    }
+}
+
+async fn read_line(stdout: &mut BufReader<ChildStdout>) -> Result<String, String> {
+    let mut buf = Vec::new();
+    let n = stdout.read_until(b'\n', &mut buf).await.map_err(|e| tf("detail.read_failed", &[("error", &e)]))?;
+    if n == 0 {
+        return Err(t("detail.process_exited"));
+    }
+    Ok(String::from_utf8_lossy(&buf).trim().to_string())
+}
+
+fn has_exited(tag: &str, process: &mut WorkerProcess) -> bool {
+    match process.child.lock().unwrap().try_wait() {
+        Ok(Some(status)) => {
+            println!("[WORKER:{}] 引擎进程已退出 ({})", tag, status);
+            true
+        }
+        _ => false,
+    }
+}
+
+fn kill_process(tag: &str, process: WorkerProcess) {
+    let mut child = process.child.lock().unwrap();
+    println!("[WORKER:{}] 关闭引擎进程, PID={}", tag, child.id().unwrap_or_default());
+    // 进程退出后由 tokio 在后台回收，不阻塞当前线程
+    let _ = child.start_kill();
+}
+
+/// 运行一次性的引擎进程并异步收集输出，超时后终止进程。`engine` 为引擎 ID。
+///
+/// 所在任务被取消时 future 被丢弃，进程同样会被终止。
+pub async fn run_with_timeout(mut command: Command, engine: &str, timeout: Duration) -> Result<Output, AppError> {
+    command.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    match tokio::time::timeout(timeout, command.output()).await {
+        Ok(Ok(output)) => Ok(output),
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::NotFound => Err(AppError::EngineMissing { engine: engine.to_string() }),
+        Ok(Err(e)) => Err(AppError::EngineCrashed { engine: engine.to_string(), detail: tf("detail.spawn_failed", &[("error", &e)]) }),
+        Err(_) => {
+            println!("[{}] 进程超过 {} 秒未结束，已终止", engine, timeout.as_secs());
+            Err(AppError::EngineTimeout { engine: engine.to_string(), secs: timeout.as_secs() })
+        }
+    }
+}
+
+/// 将 JSON 字符串中的非 ASCII 字符转义为 `\uXXXX`。
+///
+/// 部分引擎在 Windows 上以本地代码页读取标准输入，纯 ASCII 的请求可以避免中文路径乱码。
+pub fn escape_non_ascii(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    for ch in json.chars() {
+        if ch.is_ascii() {
+            out.push(ch);
+        } else {
+            let mut units = [0u16; 2];
+            for unit in ch.encode_utf16(&mut units) {
+                out.push_str(&format!("\\u{:04x}", unit));
+            }
+        }
+    }
+    out
+}
@@ -138,7 +138,7 @@ btnOcr.addEventListener('click', async () => {
         });
     } catch (err) {
         console.error("手动触发OCR失败:", err);
-        await notify("错误", `处理失败: ${err}`);
+        await notify("错误", `处理失败: ${err.message ?? err}`);
     }
 });
 
@@ -3,7 +3,7 @@
 // 导入 Tauri 核心 API，用于与 Rust 后端进行交互
 const { invoke } = window.__TAURI__.tauri;
 const { listen } = window.__TAURI__.event;
-const { message, confirm } = window.__TAURI__.dialog;
+const { message, confirm, open } = window.__TAURI__.dialog;
 
 // --- DOM 元素获取 ---
 // 将页面上所有需要操作的 HTML 元素预先获取并存入变量，方便后续使用
@@ -13,20 +13,47 @@ const shortcutInput = document.getElementById('shortcut-input');
 const viewShortcutInput = document.getElementById('view-shortcut-input');
 const targetLangSelect = document.getElementById('target-lang-select');
 const targetLangContainer = document.getElementById('target-lang-container');
+const sourceLangSelect = document.getElementById('source-lang-select');
+const sourceLangContainer = document.getElementById('source-lang-container');
+const translatorBackendSelect = document.getElementById('translator-backend-select');
+const translatorBackendContainer = document.getElementById('translator-backend-container');
+const llmSettingsBlock = document.getElementById('llm-settings');
+const llmUrlInput = document.getElementById('llm-url-input');
+const llmModelInput = document.getElementById('llm-model-input');
+const llmKeyInput = document.getElementById('llm-key-input');
+const libreSettingsBlock = document.getElementById('libretranslate-settings');
+const libreUrlInput = document.getElementById('libre-url-input');
+const libreKeyInput = document.getElementById('libre-key-input');
 const lineBreakCheckbox = document.getElementById('line-break-checkbox');
+const translationCacheCheckbox = document.getElementById('translation-cache-checkbox');
+const translationCacheStats = document.getElementById('translation-cache-stats');
+const purgeCacheBtn = document.getElementById('purge-cache-btn');
+const glossaryCount = document.getElementById('glossary-count');
+const importGlossaryBtn = document.getElementById('import-glossary-btn');
+const captureScopeSelect = document.getElementById('capture-scope-select');
+const uiLanguageSelect = document.getElementById('ui-language-select');
+const engineManifestUrlInput = document.getElementById('engine-manifest-url-input');
+const ocrEngineSelect = document.getElementById('ocr-engine-select');
+const ocrLangSelect = document.getElementById('ocr-lang-select');
 const ocrSettingsBlock = document.getElementById('ocr-settings-block');
 const radioInputs = document.getElementsByName('primary-action');
 
 // OCR 引擎管理相关元素
 const ocrEngineStatusBadge = document.getElementById('ocr-engine-status');
+const ocrEngineVersionText = document.getElementById('ocr-engine-version');
 const downloadOcrBtn = document.getElementById('download-ocr-btn');
 const ocrProgressContainer = document.getElementById('ocr-progress-container');
 const ocrProgressBar = document.getElementById('ocr-download-progress');
 const ocrProgressLabel = document.getElementById('ocr-progress-label');
+const installOcrFileBtn = document.getElementById('install-ocr-file-btn');
+const installOcrDirBtn = document.getElementById('install-ocr-dir-btn');
 
 // 翻译引擎管理相关元素
 const engineStatusBadge = document.getElementById('engine-status');
+const translatorEngineVersionText = document.getElementById('translator-engine-version');
 const downloadBtn = document.getElementById('download-btn');
+const installTranslatorFileBtn = document.getElementById('install-translator-file-btn');
+const installTranslatorDirBtn = document.getElementById('install-translator-dir-btn');
 
 const packagesProgressContainer = document.getElementById('packages-progress-container');
 const packagesProgressBar = document.getElementById('packages-download-progress');
@@ -46,12 +73,23 @@ let currentSettings = {};
 // 引擎安装状态标志
 let isOcrInstalled = false;
 let isTranslatorInstalled = false;
+// 当前翻译引擎支持的语言对 [{ source, target }]
+let supportedPairs = [];
 // 引擎下载状态标志，防止重复点击
 let isOcrDownloading = false;
 let isTranslatorDownloading = false;
 
 // --- 函数定义 ---
 
+/**
+ * 后端错误 ({ code, params, message }) 的提示文本，兼容仍以字符串返回的错误。
+ * @param {any} error
+ * @returns {string}
+ */
+function errorMessage(error) {
+    return error?.message ?? String(error);
+}
+
 /**
  * 检查本地 OCR 引擎的安装状态。
  * @async
@@ -116,6 +154,111 @@ function updateTranslatorUI() {
     }
 }
 
+/**
+ * 检查已安装引擎的版本与是否有可用的更新，并显示在引擎状态下方。
+ * @async
+ */
+async function checkEngineUpdates() {
+    const versionTexts = { rapidocr: ocrEngineVersionText, translator: translatorEngineVersionText };
+    try {
+        const engines = await invoke('check_engine_updates');
+        for (const info of engines) {
+            const text = versionTexts[info.id];
+            if (!text) continue;
+            if (!info.installed) {
+                text.textContent = `最新版本: ${info.latest_version}`;
+            } else if (!info.installed_version) {
+                text.textContent = `未记录已安装的版本，最新版本: ${info.latest_version}`;
+            } else if (info.update_available) {
+                text.textContent = `有新版本 ${info.latest_version} 可用 (当前版本: ${info.installed_version})`;
+            } else {
+                text.textContent = `已安装版本: ${info.installed_version} (已是最新)`;
+            }
+        }
+    } catch (e) {
+        console.error("[前端] 检查引擎更新失败:", e);
+        for (const text of Object.values(versionTexts)) {
+            text.textContent = "检查更新失败";
+        }
+    }
+}
+
+/**
+ * 从后端获取可用的 OCR 引擎列表，并填充引擎下拉框。
+ * @async
+ */
+async function loadOcrEngines() {
+    try {
+        const engines = await invoke('list_ocr_engines');
+        ocrEngineSelect.innerHTML = '';
+        for (const engine of engines) {
+            const option = document.createElement('option');
+            option.value = engine.id;
+            option.textContent = engine.name;
+            ocrEngineSelect.appendChild(option);
+        }
+    } catch (e) {
+        console.error("获取OCR引擎列表失败:", e);
+    }
+}
+
+/**
+ * 从后端获取当前翻译引擎支持的语言对。
+ * @async
+ */
+async function loadLanguagePairs() {
+    try {
+        supportedPairs = await invoke('get_supported_language_pairs');
+    } catch (e) {
+        console.error("获取支持的语言对失败:", e);
+        supportedPairs = [];
+    }
+    updateLanguageOptions();
+}
+
+/**
+ * 从后端获取翻译缓存的条目数与命中统计。
+ * @async
+ */
+async function loadCacheStats() {
+    try {
+        const stats = await invoke('get_translation_cache_stats');
+        const sizeKb = (stats.file_size / 1024).toFixed(1);
+        translationCacheStats.textContent = `已缓存 ${stats.entries} 条 (${sizeKb} KB)，命中 ${stats.hits} 次，未命中 ${stats.misses} 次`;
+    } catch (e) {
+        console.error("获取翻译缓存统计失败:", e);
+        translationCacheStats.textContent = "无法获取缓存统计";
+    }
+}
+
+/**
+ * 获取术语表条目数并更新显示。
+ * @async
+ */
+async function loadGlossaryCount() {
+    try {
+        const entries = await invoke('get_glossary');
+        glossaryCount.textContent = `当前共 ${entries.length} 条术语`;
+    } catch (e) {
+        console.error("获取术语表失败:", e);
+        glossaryCount.textContent = "无法获取术语表";
+    }
+}
+
+/**
+ * 根据支持的语言对，禁用无法使用的源语言与目标语言选项。
+ */
+function updateLanguageOptions() {
+    const unrestricted = supportedPairs.length === 0; // 未获取到列表时不做限制
+    const source = sourceLangSelect.value;
+    for (const option of sourceLangSelect.options) {
+        if (option.value === 'auto') continue;
+        option.disabled = !unrestricted && !supportedPairs.some(p => p.source === option.value);
+    }
+    for (const option of targetLangSelect.options) {
+        option.disabled = !unrestricted && !supportedPairs.some(p => p.target === option.value && (source === 'auto' || p.source === source));
+    }
+}
 
 /**
  * 根据用户选择的“首要动作”，动态显示或隐藏相关的设置项。
@@ -132,6 +275,10 @@ function updateUIBasedOnAction(actionValue) {
 
     // 2. 如果动作需要翻译 (或预览)，则显示“目标语言”下拉框
     targetLangContainer.classList.toggle('hidden', !requiresTranslation);
+    sourceLangContainer.classList.toggle('hidden', !requiresTranslation);
+    translatorBackendContainer.classList.toggle('hidden', !requiresTranslation);
+    llmSettingsBlock.classList.toggle('hidden', !requiresTranslation || translatorBackendSelect.value !== 'llm');
+    libreSettingsBlock.classList.toggle('hidden', !requiresTranslation || translatorBackendSelect.value !== 'libretranslate');
 
     // 3. 智能提示：如果用户选择了需要引擎的功能但未安装，则高亮下载按钮
     if (requiresOcr && !isOcrInstalled) {
@@ -159,7 +306,26 @@ async function loadSettings() {
         shortcutInput.value = settings.shortcut;
         viewShortcutInput.value = settings.view_image_shortcut;
         targetLangSelect.value = settings.target_lang;
+        sourceLangSelect.value = settings.source_lang || 'auto';
+        translatorBackendSelect.value = settings.translator_backend || 'local';
+        if (settings.llm) {
+            llmUrlInput.value = settings.llm.api_url;
+            llmModelInput.value = settings.llm.model;
+            llmKeyInput.value = settings.llm.api_key;
+        }
+        if (settings.libretranslate) {
+            libreUrlInput.value = settings.libretranslate.api_url;
+            libreKeyInput.value = settings.libretranslate.api_key;
+        }
+        await loadLanguagePairs();
         lineBreakCheckbox.checked = settings.preserve_line_breaks;
+        translationCacheCheckbox.checked = settings.translation_cache_enabled !== false;
+        captureScopeSelect.value = settings.capture_scope || 'cursor';
+        uiLanguageSelect.value = settings.ui_language || 'zh-CN';
+        engineManifestUrlInput.value = settings.engine_manifest_url || '';
+        await loadOcrEngines();
+        ocrEngineSelect.value = settings.ocr_engine || 'rapidocr';
+        ocrLangSelect.value = settings.ocr_lang || 'auto';
 
         // 根据加载的 'primary_action' 设置单选框的选中状态
         for (const radio of radioInputs) {
@@ -202,21 +368,56 @@ async function saveSettings() {
         }
     }
 
-    // 构造新的设置对象
+    // 构造新的设置对象（保留界面上未展示的其余设置项）
     const newSettings = {
+        ...currentSettings,
         shortcut: shortcutValue,
         view_image_shortcut: viewShortcutValue,
         target_lang: targetLangSelect.value,
+        source_lang: sourceLangSelect.value,
+        translator_backend: translatorBackendSelect.value,
+        llm: {
+            ...currentSettings.llm,
+            api_url: llmUrlInput.value.trim(),
+            model: llmModelInput.value.trim(),
+            api_key: llmKeyInput.value.trim(),
+        },
+        libretranslate: {
+            api_url: libreUrlInput.value.trim(),
+            api_key: libreKeyInput.value.trim(),
+        },
         preserve_line_breaks: lineBreakCheckbox.checked,
+        translation_cache_enabled: translationCacheCheckbox.checked,
         primary_action: selectedAction,
+        capture_scope: captureScopeSelect.value,
+        ui_language: uiLanguageSelect.value,
+        engine_manifest_url: engineManifestUrlInput.value.trim(),
+        ocr_engine: ocrEngineSelect.value,
+        ocr_lang: ocrLangSelect.value,
     };
 
     try {
         // 调用后端 `set_settings` 命令
         await invoke('set_settings', { settings: newSettings });
+        const backendChanged = currentSettings.translator_backend !== newSettings.translator_backend
+            || (newSettings.translator_backend === 'libretranslate'
+                && currentSettings.libretranslate?.api_url !== newSettings.libretranslate.api_url);
+        const manifestUrlChanged = currentSettings.engine_manifest_url !== newSettings.engine_manifest_url;
         currentSettings = newSettings; // 更新本地缓存
+        if (backendChanged) {
+            await loadLanguagePairs(); // 不同后端支持的语言组合不同
+        }
+        if (manifestUrlChanged) {
+            await checkEngineUpdates(); // 镜像中的清单可能包含更新的版本
+        }
     } catch (error) {
         console.error("保存设置失败:", error);
+        await message(`保存设置失败: ${errorMessage(error)}`, { title: '错误', type: 'error' });
+        // 恢复为上次成功保存的语言设置
+        sourceLangSelect.value = currentSettings.source_lang || 'auto';
+        targetLangSelect.value = currentSettings.target_lang;
+        translatorBackendSelect.value = currentSettings.translator_backend || 'local';
+        updateLanguageOptions();
     }
 }
 
@@ -268,7 +469,7 @@ downloadOcrBtn.addEventListener('click', async () => {
         await invoke('download_ocr');
     } catch (e) {
         console.error("[前端] 后端 download_ocr 调用失败:", e);
-        await message(`下载失败: ${e}`, { title: '错误', type: 'error' });
+        await message(`下载失败: ${errorMessage(e)}`, { title: '错误', type: 'error' });
         // 下载失败后，重置 UI 状态
         isOcrDownloading = false;
         downloadOcrBtn.disabled = false;
@@ -304,7 +505,7 @@ downloadBtn.addEventListener('click', async () => {
         await invoke('download_translator');
     } catch (e) {
         console.error("翻译引擎下载出错:", e);
-        await message(`下载失败: ${e}`, { title: '错误', type: 'error' });
+        await message(`下载失败: ${errorMessage(e)}`, { title: '错误', type: 'error' });
         isTranslatorDownloading = false;
         downloadBtn.disabled = false;
         updateTranslatorUI();
@@ -313,6 +514,64 @@ downloadBtn.addEventListener('click', async () => {
     }
 });
 
+/**
+ * 从本地安装包或已解压的文件夹安装引擎，供无法联网的环境使用。
+ * 安装进度通过与在线下载相同的事件更新界面。
+ * @async
+ * @param {'ocr' | 'translator'} engine - 要安装的引擎。
+ * @param {boolean} directory - 是否选择文件夹而不是安装包文件。
+ */
+async function installEngineFromLocal(engine, directory) {
+    const isOcr = engine === 'ocr';
+    if (isOcr ? isOcrDownloading : isTranslatorDownloading) return;
+
+    const path = await open(directory
+        ? { directory: true, multiple: false }
+        : {
+            multiple: false,
+            filters: [{ name: '引擎安装包', extensions: isOcr ? ['7z', 'zip'] : ['7z', 'zip', 'exe'] }],
+        });
+    if (!path) return;
+
+    if (isOcr) {
+        isOcrDownloading = true;
+        downloadOcrBtn.disabled = true;
+        ocrProgressContainer.style.display = 'block';
+        ocrProgressLabel.textContent = "正在读取安装包...";
+    } else {
+        isTranslatorDownloading = true;
+        downloadBtn.disabled = true;
+        packagesProgressContainer.style.display = 'block';
+        packagesProgressLabel.textContent = "正在读取安装包...";
+        engineProgressContainer.style.display = 'block';
+        engineProgressLabel.textContent = "等待中...";
+    }
+
+    try {
+        await invoke('install_engine_from_path', { engine, path });
+    } catch (e) {
+        console.error("[前端] 本地安装失败:", e);
+        await message(`安装失败: ${errorMessage(e)}`, { title: '错误', type: 'error' });
+        if (isOcr) {
+            isOcrDownloading = false;
+            downloadOcrBtn.disabled = false;
+            updateOcrUI();
+            ocrProgressContainer.style.display = 'none';
+        } else {
+            isTranslatorDownloading = false;
+            downloadBtn.disabled = false;
+            updateTranslatorUI();
+            packagesProgressContainer.style.display = 'none';
+            engineProgressContainer.style.display = 'none';
+        }
+    }
+}
+
+installOcrFileBtn.addEventListener('click', () => installEngineFromLocal('ocr', false));
+installOcrDirBtn.addEventListener('click', () => installEngineFromLocal('ocr', true));
+installTranslatorFileBtn.addEventListener('click', () => installEngineFromLocal('translator', false));
+installTranslatorDirBtn.addEventListener('click', () => installEngineFromLocal('translator', true));
+
 // 3. 监听后端发送的 OCR 下载进度事件
 listen('ocr-download-progress', (event) => {
     console.log("[前端] 收到 'ocr-download-progress' 事件, payload:", JSON.stringify(event.payload));
@@ -324,9 +583,19 @@ listen('ocr-download-progress', (event) => {
         const downloadedMB = (progress / 1024 / 1024).toFixed(1);
         const totalMB = (total / 1024 / 1024).toFixed(1);
         ocrProgressLabel.textContent = `正在下载... ${percent}% (${downloadedMB}MB / ${totalMB}MB)`;
+    } else if (status === 'retrying') {
+        // 网络中断，后端正在重试并将从断点续传
+        ocrProgressLabel.textContent = `连接中断，正在重试... (已下载 ${(progress / 1024 / 1024).toFixed(1)}MB)`;
+    } else if (status === 'verifying') {
+        ocrProgressBar.removeAttribute('value');
+        ocrProgressLabel.textContent = "下载完成，正在校验文件完整性...";
     } else if (status === 'extracting') {
         ocrProgressBar.removeAttribute('value'); // 进入不确定进度状态
         ocrProgressLabel.textContent = "下载完成，正在解压安装，请稍候...";
+    } else if (status === 'testing') {
+        // 新版本未通过自检时后端会恢复到之前的版本并返回错误
+        ocrProgressBar.removeAttribute('value');
+        ocrProgressLabel.textContent = "正在检查引擎能否正常运行...";
     } else if (status === 'completed') {
         ocrProgressBar.value = 100;
         ocrProgressLabel.textContent = "安装完成！";
@@ -334,6 +603,7 @@ listen('ocr-download-progress', (event) => {
         isOcrInstalled = true;
         downloadOcrBtn.disabled = false;
         updateOcrUI();
+        checkEngineUpdates();
         console.log("[前端] OCR 引擎安装完成.");
 
         setTimeout(() => { ocrProgressContainer.style.display = 'none'; }, 2000);
@@ -363,9 +633,17 @@ listen('download-progress', (event) => {
         const downloadedMB = (progress / 1024 / 1024).toFixed(1);
         const totalMB = (total / 1024 / 1024).toFixed(1);
         currentLabel.textContent = `正在下载... ${percent}% (${downloadedMB}MB / ${totalMB}MB)`;
+    } else if (status === 'retrying') {
+        currentLabel.textContent = `连接中断，正在重试... (已下载 ${(progress / 1024 / 1024).toFixed(1)}MB)`;
+    } else if (status === 'verifying') {
+        currentBar.removeAttribute('value');
+        currentLabel.textContent = "下载完成，正在校验文件完整性...";
     } else if (status === 'extracting') {
         currentBar.removeAttribute('value');
         currentLabel.textContent = "下载完成，正在解压安装，请稍候...";
+    } else if (status === 'testing') {
+        currentBar.removeAttribute('value');
+        currentLabel.textContent = "正在检查引擎能否正常运行...";
     } else if (status === 'completed') {
         currentBar.value = 100;
         currentLabel.textContent = "处理完成！";
@@ -376,6 +654,8 @@ listen('download-progress', (event) => {
             isTranslatorInstalled = true;
             downloadBtn.disabled = false;
             updateTranslatorUI();
+            loadLanguagePairs(); // 新安装的语言包可能改变支持的语言组合
+            checkEngineUpdates();
             setTimeout(() => { 
                 packagesProgressContainer.style.display = 'none'; 
                 engineProgressContainer.style.display = 'none';
@@ -394,7 +674,55 @@ for (const radio of radioInputs) {
     });
 }
 targetLangSelect.addEventListener('change', saveSettings);
+translatorBackendSelect.addEventListener('change', () => {
+    llmSettingsBlock.classList.toggle('hidden', translatorBackendSelect.value !== 'llm');
+    libreSettingsBlock.classList.toggle('hidden', translatorBackendSelect.value !== 'libretranslate');
+    saveSettings();
+});
+llmUrlInput.addEventListener('change', saveSettings);
+llmModelInput.addEventListener('change', saveSettings);
+llmKeyInput.addEventListener('change', saveSettings);
+libreUrlInput.addEventListener('change', saveSettings);
+libreKeyInput.addEventListener('change', saveSettings);
+sourceLangSelect.addEventListener('change', () => {
+    updateLanguageOptions();
+    saveSettings();
+});
 lineBreakCheckbox.addEventListener('change', saveSettings);
+translationCacheCheckbox.addEventListener('change', saveSettings);
+importGlossaryBtn.addEventListener('click', async () => {
+    const path = await open({
+        multiple: false,
+        filters: [{ name: '术语表', extensions: ['csv', 'tsv', 'txt'] }],
+    });
+    if (!path) return;
+    try {
+        const count = await invoke('import_glossary', {
+            path,
+            sourceLang: sourceLangSelect.value,
+            targetLang: targetLangSelect.value,
+        });
+        await message(`成功导入 ${count} 条术语。`, { title: '导入完成', type: 'info' });
+    } catch (e) {
+        await message(`导入术语表失败: ${errorMessage(e)}`, { title: '错误', type: 'error' });
+    }
+    await loadGlossaryCount();
+});
+purgeCacheBtn.addEventListener('click', async () => {
+    const confirmed = await confirm('确定要清空所有已缓存的翻译结果吗？', { title: '清空翻译缓存', type: 'warning' });
+    if (!confirmed) return;
+    try {
+        await invoke('purge_translation_cache');
+    } catch (e) {
+        await message(`清空翻译缓存失败: ${errorMessage(e)}`, { title: '错误', type: 'error' });
+    }
+    await loadCacheStats();
+});
+captureScopeSelect.addEventListener('change', saveSettings);
+uiLanguageSelect.addEventListener('change', saveSettings);
+engineManifestUrlInput.addEventListener('change', saveSettings);
+ocrEngineSelect.addEventListener('change', saveSettings);
+ocrLangSelect.addEventListener('change', saveSettings);
 
 // 6. 快捷键输入框的交互逻辑
 shortcutInput.addEventListener('focus', () => {
@@ -457,7 +785,10 @@ async function initialize() {
     await Promise.all([
         loadSettings(),          // 从后端加载并应用设置
         checkOcrStatus(),        // 检查 OCR 引擎状态
-        checkTranslatorStatus()  // 检查翻译引擎状态
+        checkTranslatorStatus(), // 检查翻译引擎状态
+        checkEngineUpdates(),    // 检查引擎版本与可用的更新
+        loadCacheStats(),        // 获取翻译缓存统计
+        loadGlossaryCount()      // 获取术语表条目数
     ]);
     console.log("前端初始化完成。");
 }
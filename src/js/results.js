@@ -68,11 +68,24 @@ function speakText() {
 
 // --- 核心修改：重构后的 UI 更新逻辑 ---
 
+/**
+ * 当前显示的识别结果所属的任务 ID，只接收该任务推送的译文
+ */
+let currentJobId = null;
+
+/**
+ * 推送的译文是否属于当前显示的识别结果（截图后未打开结果窗口时，新任务的译文不应覆盖旧结果）
+ */
+function isCurrentJob(payload) {
+    return (payload.job_id ?? null) === currentJobId;
+}
+
 /**
  * 处理 OCR 结果数据并更新 UI
  */
 function handleOcrResultPayload(payload) {
     console.log("[RESULTS.JS] 处理 OCR 数据:", payload);
+    currentJobId = payload.job_id ?? null;
 
     // 重置译文区域颜色
     translatedTextEl.style.color = 'var(--text-color-bright)';
@@ -89,13 +102,13 @@ function handleOcrResultPayload(payload) {
     }
 
     // 2. 根据是否有错误来更新文本区域
-    if (payload.error_message) {
+    if (payload.error) {
         originalTextContent = payload.original_text || "错误";
-        translatedTextContent = payload.error_message;
+        translatedTextContent = payload.error.message;
 
         originalTextEl.textContent = originalTextContent;
         translatedTextEl.textContent = translatedTextContent;
-        translatedTextEl.style.color = 'var(--error-color)';
+        translatedTextEl.style.color = errorColor(payload.error);
     } else {
         originalTextContent = payload.original_text || '';
         // 只有当译文内容为空时（即刚识别完），才显示“翻译中...”
@@ -107,21 +120,30 @@ function handleOcrResultPayload(payload) {
     }
 }
 
+/**
+ * 后端错误 ({ code, params, message }) 的显示颜色：用户主动取消不算错误
+ */
+function errorColor(error) {
+    return error.code === 'cancelled' ? 'var(--text-color-dim)' : 'var(--error-color)';
+}
+
 /**
  * 处理翻译结果数据并更新 UI
  */
 function handleTranslationUpdatePayload(payload) {
     console.log("[RESULTS.JS] 处理翻译数据:", payload);
 
-    if (payload.error_message) {
-        translatedTextContent = payload.error_message;
+    if (payload.error) {
+        translatedTextContent = payload.error.message;
         translatedTextEl.textContent = translatedTextContent;
-        translatedTextEl.style.color = 'var(--error-color)';
+        translatedTextEl.style.color = errorColor(payload.error);
     } else {
         translatedTextContent = payload.translated_text || '';
         translatedTextEl.textContent = translatedTextContent;
         translatedTextEl.style.color = 'var(--text-color-bright)';
     }
+    // 鼠标悬停时显示实际完成翻译的后端（配置了备用后端时可能不是首选后端）
+    translatedTextEl.title = payload.translator_backend ? `翻译后端: ${payload.translator_backend}` : '';
 }
 
 
@@ -133,7 +155,20 @@ listen('ocr_result', (event) => {
     handleOcrResultPayload(event.payload);
 });
 
+// 翻译进行中：按片段或逐字推送的阶段性译文
+listen('translation_progress', (event) => {
+    if (!isCurrentJob(event.payload)) return;
+    const { translated_text, completed, total } = event.payload;
+    if (!translated_text) return;
+    translatedTextContent = translated_text;
+    translatedTextEl.textContent = translatedTextContent;
+    translatedTextEl.style.color = 'var(--text-color-bright)';
+    translatedTextEl.title = total > 1 ? `翻译中 (${completed}/${total})` : '翻译中';
+});
+
+// 翻译完成（或失败）时推送的最终结果
 listen('translation_update', (event) => {
+    if (!isCurrentJob(event.payload)) return;
     handleTranslationUpdatePayload(event.payload);
 });
 
@@ -174,7 +209,7 @@ copyImageBtn.addEventListener('click', async () => {
         await notify('复制成功', '截图已复制到剪贴板。');
     } catch (error) {
         console.error("复制图片失败:", error);
-        await notify('复制失败', `错误: ${error}`);
+        await notify('复制失败', `错误: ${error?.message ?? error}`);
     }
 });
 
@@ -186,7 +221,7 @@ saveImageBtn.addEventListener('click', async () => {
         await notify('保存成功', '截图已保存到桌面。');
     } catch (error) {
         console.error("保存图片失败:", error);
-        await notify('保存失败', `错误: ${error}`);
+        await notify('保存失败', `错误: ${error?.message ?? error}`);
     }
 });
 
@@ -209,8 +244,9 @@ async function init() {
 
             // 1. 恢复 OCR 数据
             handleOcrResultPayload({
+                job_id: cached.job_id,
                 original_text: cached.original_text,
-                error_message: null,
+                error: null,
                 image_path: cached.image_path
             });
 
@@ -218,7 +254,8 @@ async function init() {
             if (cached.translated_text) {
                 handleTranslationUpdatePayload({
                     translated_text: cached.translated_text,
-                    error_message: null
+                    error: null,
+                    translator_backend: cached.translator_backend
                 });
             }
         }